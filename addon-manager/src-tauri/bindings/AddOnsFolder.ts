@@ -1,9 +1,13 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AddonRepository } from './AddonRepository'
+import type { FolderSettings } from './FolderSettings'
+import type { OrphanReport } from './OrphanReport'
 
 export type AddOnsFolder = {
     path: string
     isValid: boolean
     error: string | null
     repositories: Array<AddonRepository>
+    orphans: OrphanReport
+    settings: FolderSettings
 }
@@ -1,5 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AddonRepositoryUserMeta } from './AddonRepositoryUserMeta'
+import type { AutoUpdatePolicy } from './AutoUpdatePolicy'
+import type { LinkStrategy } from './LinkStrategy'
 
 /**
  * User metadata for a single AddOns directory
@@ -10,4 +12,26 @@ export type AddOnsFolderUserMeta = {
      */
     path: string
     repos: Array<AddonRepositoryUserMeta>
+    /**
+     * Shown instead of the path
+     */
+    displayName: string | null
+    /**
+     * Game flavor or client version, e.g. `3.3.5.12340`
+     */
+    clientVersion: string | null
+    /**
+     * How sub-addons are placed into this folder; symlinks when unset
+     */
+    linkStrategy: LinkStrategy | null
+    autoUpdate: AutoUpdatePolicy | null
+    /**
+     * Branch checked out on install when the repository has it, e.g. a realm
+     * specific branch; the remote default otherwise
+     */
+    preferredBranch: string | null
+    /**
+     * Repository URL globs skipped by bulk updates in this folder
+     */
+    excludedRepos: Array<string>
 }
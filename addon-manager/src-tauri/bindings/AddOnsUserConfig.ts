@@ -1,5 +1,6 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AddOnsFolderUserMeta } from './AddOnsFolderUserMeta'
+import type { LinkStyle } from './LinkStyle'
 
 /**
  * user configuration stored in Tauri store
@@ -13,4 +14,20 @@ export type AddOnsUserConfig = {
      * Selected theme name
      */
     theme: string | null
+    /**
+     * Days deleted addons stay in the trash before being purged
+     */
+    trashRetentionDays: number | null
+    /**
+     * SavedVariables snapshots kept per repository before updates
+     */
+    savedVariablesBackupCount: number | null
+    /**
+     * Whether new links are absolute or relative to the AddOns folder
+     */
+    linkStyle: LinkStyle | null
+    /**
+     * Minutes between background update checks; disabled when unset or 0
+     */
+    updateCheckIntervalMinutes: number | null
 }
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AddonErrorKind } from './AddonErrorKind'
+
+export type AddonError = {
+    kind: AddonErrorKind
+    /**
+     * Human readable summary of what failed
+     */
+    message: string
+    /**
+     * Affected file or directory
+     */
+    path: string | null
+    /**
+     * Affected repository URL
+     */
+    url: string | null
+    /**
+     * Underlying error message
+     */
+    cause: string | null
+}
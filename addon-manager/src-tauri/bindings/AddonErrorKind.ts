@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Machine-readable error category so the UI and CLI can react per kind
+ */
+export type AddonErrorKind =
+    | 'network'
+    | 'notFound'
+    | 'permissionDenied'
+    | 'invalidInput'
+    | 'locked'
+    | 'gameRunning'
+    | 'cancelled'
+    | 'git'
+    | 'io'
+    | 'config'
+    | 'other'
@@ -4,4 +4,12 @@ import type { AddonUserMeta } from './AddonUserMeta'
 export type AddonRepositoryUserMeta = {
     repoUrl: string
     addons: { [key in string]?: AddonUserMeta }
+    /**
+     * Skipped by "update all" and headless updates, it can still be updated on its own
+     */
+    excludeFromBulkUpdate: boolean
+    /**
+     * Updated by scheduled update checks without asking
+     */
+    autoUpdate: boolean
 }
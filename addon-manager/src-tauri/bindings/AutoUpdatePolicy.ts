@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Which repositories of a folder scheduled update checks update without asking
+ */
+export type AutoUpdatePolicy = 'perRepository' | 'all' | 'never'
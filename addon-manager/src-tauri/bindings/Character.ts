@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A character found in `WTF/Account/<account>/<realm>/<name>`
+ */
+export type Character = {
+    account: string
+    realm: string
+    name: string
+}
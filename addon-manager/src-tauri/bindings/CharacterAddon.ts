@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A managed sub-addon and whether it is enabled for a character
+ */
+export type CharacterAddon = {
+    name: string
+    repoName: string
+    /**
+     * Addons missing from `AddOns.txt` are enabled by the client
+     */
+    enabled: boolean
+}
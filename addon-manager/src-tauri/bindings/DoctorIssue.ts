@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IssueKind } from './IssueKind'
+import type { IssueSeverity } from './IssueSeverity'
+
+/**
+ * A single problem found by `diagnose_folder`
+ */
+export type DoctorIssue = {
+    severity: IssueSeverity
+    kind: IssueKind
+    /**
+     * Repository directory name in `.addonmanager`, if the issue belongs to one
+     */
+    repo: string | null
+    path: string
+    message: string
+    /**
+     * Suggested way to fix the issue
+     */
+    fix: string
+}
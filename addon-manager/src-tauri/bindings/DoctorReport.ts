@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FolderDiagnosis } from './FolderDiagnosis'
+
+/**
+ * Health of every configured AddOns folder
+ */
+export type DoctorReport = {
+    folders: Array<FolderDiagnosis>
+}
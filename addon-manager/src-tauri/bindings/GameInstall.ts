@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A game installation with an AddOns folder found on disk
+ */
+export type GameInstall = {
+    /**
+     * The `Interface/AddOns` folder
+     */
+    addonsPath: string
+    gamePath: string
+    /**
+     * Client executable in the game directory
+     */
+    executable: string | null
+    /**
+     * File version of the client executable, e.g. `3.3.5.12340`
+     */
+    clientVersion: string | null
+    /**
+     * Whether the AddOns folder is already managed
+     */
+    configured: boolean
+}
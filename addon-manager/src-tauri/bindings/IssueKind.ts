@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type IssueKind =
+    | 'invalidFolder'
+    | 'unreadableRepo'
+    | 'corruptObjects'
+    | 'missingOrigin'
+    | 'originMismatch'
+    | 'detachedHead'
+    | 'brokenLink'
+    | 'unreadableToc'
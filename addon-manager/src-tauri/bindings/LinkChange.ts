@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LinkChangeKind } from './LinkChangeKind'
+
+/**
+ * A change made by `repair_folder` to a single link
+ */
+export type LinkChange = {
+    kind: LinkChangeKind
+    repoName: string
+    /**
+     * Link path in the AddOns folder
+     */
+    link: string
+    target: string
+    reason: string | null
+}
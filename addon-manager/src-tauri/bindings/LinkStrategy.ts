@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How sub-addons are made visible in the AddOns folder
+ */
+export type LinkStrategy = 'symlink' | 'hardlinkTree' | 'copy'
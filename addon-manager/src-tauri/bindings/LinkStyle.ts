@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How links in the AddOns folder refer to their target
+ */
+export type LinkStyle = 'absolute' | 'relative'
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AddonError } from './AddonError'
 import type { OperationType } from './OperationType'
 
 export type OperationEvent =
@@ -6,5 +7,6 @@ export type OperationEvent =
     | { progress: { current: number; total: number } }
     | { status: string }
     | { warning: string }
-    | { error: string }
+    | { error: AddonError }
     | 'completed'
+    | 'cancelled'
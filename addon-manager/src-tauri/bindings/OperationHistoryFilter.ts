@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type OperationHistoryFilter = {
+    folderPath: string | null
+    repoUrl: string | null
+    /**
+     * Only include operations started at or after this RFC 3339 time
+     */
+    since: string | null
+    /**
+     * Only include operations started at or before this RFC 3339 time
+     */
+    until: string | null
+}
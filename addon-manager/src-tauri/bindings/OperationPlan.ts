@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OperationKey } from './OperationKey'
+import type { OperationType } from './OperationType'
+import type { PlannedAction } from './PlannedAction'
+
+/**
+ * Actions an operation would perform for a single repository
+ */
+export type OperationPlan = {
+    key: OperationKey
+    operation: OperationType
+    actions: Array<PlannedAction>
+}
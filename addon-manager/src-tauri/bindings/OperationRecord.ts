@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AddonError } from './AddonError'
+import type { OperationKey } from './OperationKey'
+import type { OperationStatus } from './OperationStatus'
+import type { OperationType } from './OperationType'
+
+/**
+ * A finished operation as persisted in the history log
+ */
+export type OperationRecord = {
+    key: OperationKey
+    operation: OperationType
+    startedAt: string
+    finishedAt: string
+    status: OperationStatus
+    messages: Array<string>
+    warnings: Array<string>
+    errors: Array<AddonError>
+    /**
+     * Repository HEAD before the operation started
+     */
+    refBefore: string | null
+    /**
+     * Repository HEAD after the operation finished
+     */
+    refAfter: string | null
+}
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Which kinds of orphans `cleanup_orphans` should fix
+ */
+export type OrphanCleanup = {
+    /**
+     * Remove broken links
+     */
+    brokenLinks: boolean
+    /**
+     * Remove links pointing outside of `.addonmanager`; their targets are left alone
+     */
+    foreignLinks: boolean
+    /**
+     * Move repositories without links into the trash
+     */
+    unlinkedRepos: boolean
+}
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A symlink in the AddOns folder that does not belong to a managed repository
+ */
+export type OrphanLink = {
+    /**
+     * Symlink name in the AddOns folder
+     */
+    name: string
+    /**
+     * Resolved link target
+     */
+    target: string
+}
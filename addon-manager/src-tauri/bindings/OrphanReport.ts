@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OrphanLink } from './OrphanLink'
+
+/**
+ * Leftovers of repositories removed or linked outside of the manager
+ */
+export type OrphanReport = {
+    /**
+     * Links whose target no longer exists
+     */
+    brokenLinks: Array<OrphanLink>
+    /**
+     * Links pointing outside of `.addonmanager`
+     */
+    foreignLinks: Array<OrphanLink>
+    /**
+     * Repositories in `.addonmanager` without any link in the AddOns folder
+     */
+    unlinkedRepos: Array<string>
+    /**
+     * Entries a cleanup could not fix, with the reason
+     */
+    errors: Array<string>
+}
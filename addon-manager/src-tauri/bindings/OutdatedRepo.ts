@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A repository whose local branch is behind the last fetched remote ref
+ */
+export type OutdatedRepo = {
+    folderPath: string
+    repoUrl: string
+    repoName: string
+    branch: string
+    localRef: string
+    remoteRef: string
+    /**
+     * Commits on the remote branch missing locally, if the history could be compared
+     */
+    behind: number | null
+}
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A single change an operation would make, computed without touching disk
+ */
+export type PlannedAction =
+    | { clone: { url: string; path: string } }
+    | { replaceDirectory: { path: string } }
+    | { createLink: { link: string; target: string } }
+    | { linkDiscoveredAddons: { path: string } }
+    | { removeLink: { link: string; target: string } }
+    | { fetch: { url: string; branch: string } }
+    | { resetBranch: { branch: string; from: string | null; to: string | null } }
+    | { moveToTrash: { path: string; destination: string } }
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A snapshot of a repository's saved variables, stored in
+ * `.addonmanager/.backups/<repo>/<id>`
+ */
+export type SavedVariablesBackup = {
+    /**
+     * Timestamp based directory name
+     */
+    id: string
+    repoName: string
+    createdAt: string
+    /**
+     * Repository HEAD when the snapshot was taken
+     */
+    repoRef: string | null
+    /**
+     * Files relative to the `WTF` directory
+     */
+    files: Array<string>
+}
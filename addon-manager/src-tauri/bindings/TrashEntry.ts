@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TrashedLink } from './TrashedLink'
+
+/**
+ * A deleted repository kept in `.addonmanager/.trash/<id>`
+ */
+export type TrashEntry = {
+    /**
+     * `<repo>-<timestamp>`, the directory name in the trash
+     */
+    id: string
+    repoName: string
+    repoUrl: string
+    deletedAt: string
+    links: Array<TrashedLink>
+}
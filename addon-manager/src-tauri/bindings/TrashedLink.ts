@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A symlink in the AddOns folder that pointed into a deleted repository
+ */
+export type TrashedLink = {
+    /**
+     * Symlink name in the AddOns folder
+     */
+    name: string
+    /**
+     * Link target relative to the repository root
+     */
+    target: string
+}
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OperationKey } from './OperationKey'
+import type { OutdatedRepo } from './OutdatedRepo'
+
+/**
+ * Result of a scheduled update check
+ */
+export type UpdateCheckSummary = {
+    checkedAt: string
+    /**
+     * Outdated repositories left for the user to update
+     */
+    outdated: Array<OutdatedRepo>
+    /**
+     * Repositories flagged auto-update that were updated
+     */
+    updated: Array<OperationKey>
+    /**
+     * Repositories flagged auto-update whose update failed
+     */
+    failed: Array<OperationKey>
+    /**
+     * Folders whose game client was running; they were not checked
+     */
+    skippedFolders: Array<string>
+}
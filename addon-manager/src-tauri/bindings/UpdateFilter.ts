@@ -0,0 +1,32 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Restricts which repositories "update all" touches. Empty include lists match
+ * everything; excludes win over includes.
+ */
+export type UpdateFilter = {
+    /**
+     * Only update repositories in these AddOns folders
+     */
+    folders: Array<string>
+    /**
+     * Skip repositories in these AddOns folders
+     */
+    excludeFolders: Array<string>
+    /**
+     * Only update repositories whose URL matches one of these globs, e.g. `*github.com/owner/*`
+     */
+    repos: Array<string>
+    /**
+     * Skip repositories whose URL matches one of these globs
+     */
+    excludeRepos: Array<string>
+    /**
+     * Only update repositories of these owners
+     */
+    owners: Array<string>
+    /**
+     * Skip repositories of these owners
+     */
+    excludeOwners: Array<string>
+}
@@ -1,6 +1,9 @@
 use std::sync::RwLockReadGuard;
 
 use crate::addon_disk::{self, DiskAddOnsFolder, DiskAddonSource, get_source_key};
+use crate::addon_store::AddOnsUserConfig;
+use crate::operation_registry::OperationRegistry;
+use crate::operation_reporter::OperationKey;
 use crate::view_models;
 use tauri::AppHandle;
 
@@ -17,6 +20,20 @@ impl AppState {
     ) -> Result<std::sync::RwLockReadGuard<'_, HashMap<String, DiskAddOnsFolder>>, String> {
         self.disk_state.read().map_err(|e| e.to_string())
     }
+
+    /// Replace the scan data of the given folders, keeping the others
+    pub fn update_folders(&self, folders: impl IntoIterator<Item = DiskAddOnsFolder>) {
+        let mut map = match self.disk_state.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                eprintln!("RwLock poisoned: {poisoned:?}");
+                poisoned.into_inner()
+            }
+        };
+        for folder in folders {
+            map.insert(folder.path.clone(), folder);
+        }
+    }
 }
 
 impl Default for AppState {
@@ -39,31 +56,49 @@ pub async fn refresh_addon_data(
 
 #[tauri::command]
 /// Check for updates - scan with remote fetch operations
+/// Each folder check can be cancelled with a key that has an empty repo URL
 pub async fn check_for_updates(
     app: AppHandle,
     state: tauri::State<'_, AppState>,
+    registry: tauri::State<'_, OperationRegistry>,
 ) -> Result<Vec<view_models::AddOnsFolder>, String> {
     // Read configured addon directories
     let config = crate::addon_store::load_user_config(&app)?;
 
-    // Clone the config to move it into the spawn_blocking task
-    let folders = config.folders.clone();
+    // Register a cancellation token per folder and move them into the spawn_blocking task
+    let folders: Vec<_> = config
+        .folders
+        .iter()
+        .map(|f| {
+            let key = OperationKey {
+                repo_url: String::new(),
+                folder_path: f.path.clone(),
+            };
+            let cancel = registry.register(&key);
+            (key, cancel)
+        })
+        .collect();
+    let tokens = folders.clone();
 
     // Perform the disk scanning with remote fetch in a background thread
     let disk_results = tauri::async_runtime::spawn_blocking(move || {
         let mut results = Vec::new();
-        for folder_meta in &folders {
-            let path = &folder_meta.path;
-            let folder = DiskAddOnsFolder::scan(path).unwrap_or_else(|e| {
-                eprintln!("Failed to scan path {path:?}: {e:?}");
-                DiskAddOnsFolder::default_with_error(path, e)
-            });
+        for (key, cancel) in &folders {
+            let path = &key.folder_path;
+            let folder =
+                DiskAddOnsFolder::scan_with_cancel(path, cancel, &app).unwrap_or_else(|e| {
+                    eprintln!("Failed to scan path {path:?}: {e:?}");
+                    DiskAddOnsFolder::default_with_error(path, e.to_string())
+                });
             results.push((path.clone(), folder));
         }
         results
     })
-    .await
-    .map_err(|e| format!("Task join error: {e}"))?;
+    .await;
+    for (key, cancel) in &tokens {
+        registry.unregister(key, cancel);
+    }
+    let disk_results = disk_results.map_err(|e| format!("Task join error: {e}"))?;
 
     // Update in-memory disk data
     {
@@ -84,77 +119,7 @@ pub async fn check_for_updates(
     let disk_map = guard.clone();
 
     // Merge disk + user‐meta
-    let merged: Vec<view_models::AddOnsFolder> = disk_map
-        .into_iter()
-        .map(|(path, disk_folder)| {
-            // find the matching folder user‐meta (if any)
-            let folder_meta = config.folders.iter().find(|f| f.path == path);
-
-            let repos = disk_folder
-                .repositories
-                .into_iter()
-                .map(|disk_repo| {
-                    // find the matching repo user‐meta by source key
-                    let source_key = get_source_key(&disk_repo.source);
-                    let user_repo =
-                        folder_meta.and_then(|f| f.repos.iter().find(|r| r.repo_url == source_key));
-
-                    // build addons + user‐meta
-                    let addons = disk_repo
-                        .addons
-                        .into_iter()
-                        .map(|disk_addon| {
-                            let user_addon = user_repo.and_then(|r| r.addons.get(&disk_addon.name));
-                            view_models::Addon {
-                                name: disk_addon.name.clone(),
-                                names: disk_addon.names,
-                                dir: disk_addon.dir,
-                                notes: disk_addon.notes,
-                                is_symlinked: disk_addon.is_symlinked,
-                                custom_name: user_addon.and_then(|m| m.name.clone()),
-                            }
-                        })
-                        .collect();
-
-                    let source = match disk_repo.source {
-                        addon_disk::DiskAddonSource::Git {
-                            repo_url,
-                            owner,
-                            repo_name,
-                            current_branch,
-                            available_branches,
-                            repo_ref,
-                            latest_ref,
-                            readme,
-                        } => view_models::AddonSource::Git {
-                            repo_url,
-                            owner,
-                            repo_name,
-                            current_branch,
-                            available_branches,
-                            repo_ref,
-                            latest_ref,
-                            readme,
-                        },
-                        addon_disk::DiskAddonSource::Local { folder_name, path } => {
-                            view_models::AddonSource::Local { folder_name, path }
-                        }
-                    };
-
-                    view_models::AddonRepository { source, addons }
-                })
-                .collect();
-
-            view_models::AddOnsFolder {
-                path: disk_folder.path,
-                is_valid: disk_folder.is_valid,
-                error: disk_folder.error,
-                repositories: repos,
-            }
-        })
-        .collect();
-
-    Ok(merged)
+    Ok(merge_user_meta(disk_map.into_values(), &config))
 }
 
 #[tauri::command]
@@ -182,7 +147,7 @@ pub fn refresh_disk_data(
             let path = &folder_meta.path;
             let mut folder = DiskAddOnsFolder::scan_disk_only(path).unwrap_or_else(|e| {
                 eprintln!("Failed to scan path {path:?}: {e:?}");
-                DiskAddOnsFolder::default_with_error(path, e)
+                DiskAddOnsFolder::default_with_error(path, e.to_string())
             });
 
             // Preserve latest_ref from previous state for each repo
@@ -222,11 +187,19 @@ pub fn refresh_disk_data(
     let disk_map = guard.clone();
 
     // Merge disk + user‐meta
-    let merged: Vec<view_models::AddOnsFolder> = disk_map
+    Ok(merge_user_meta(disk_map.into_values(), &config))
+}
+
+/// Combine scanned folders with the user metadata of their repositories and addons
+pub fn merge_user_meta(
+    disk_folders: impl IntoIterator<Item = DiskAddOnsFolder>,
+    config: &AddOnsUserConfig,
+) -> Vec<view_models::AddOnsFolder> {
+    disk_folders
         .into_iter()
-        .map(|(path, disk_folder)| {
+        .map(|disk_folder| {
             // find the matching folder user‐meta (if any)
-            let folder_meta = config.folders.iter().find(|f| f.path == path);
+            let folder_meta = config.folders.iter().find(|f| f.path == disk_folder.path);
 
             let repos = disk_folder
                 .repositories
@@ -247,8 +220,8 @@ pub fn refresh_disk_data(
                                 name: disk_addon.name.clone(),
                                 names: disk_addon.names,
                                 dir: disk_addon.dir,
-                                is_symlinked: disk_addon.is_symlinked,
                                 notes: disk_addon.notes,
+                                is_symlinked: disk_addon.is_symlinked,
                                 custom_name: user_addon.and_then(|m| m.name.clone()),
                             }
                         })
@@ -283,14 +256,15 @@ pub fn refresh_disk_data(
                 })
                 .collect();
 
+            let settings = folder_meta.map(|f| f.settings.clone()).unwrap_or_default();
             view_models::AddOnsFolder {
+                settings,
                 path: disk_folder.path,
                 is_valid: disk_folder.is_valid,
                 error: disk_folder.error,
                 repositories: repos,
+                orphans: disk_folder.orphans,
             }
         })
-        .collect();
-
-    Ok(merged)
+        .collect()
 }
@@ -1,11 +1,18 @@
-use std::path::Path;
+use std::{cell::Cell, path::Path};
 
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::{ffi::OsStr, path::PathBuf};
 
+use crate::deploy;
+use crate::error::AddonError;
 use crate::git;
-use crate::symlink;
+use crate::operation_registry::CancellationToken;
+use crate::operation_reporter::{
+    EventSink, OperationEvent, OperationKey, StderrEvents, emit_operation_event,
+};
+use crate::orphans::{self, OrphanReport};
+use crate::retry::{self, RetryPolicy};
 
 /// Helper to extract a unique identifier from DiskAddonSource for matching user metadata
 pub fn get_source_key(source: &DiskAddonSource) -> String {
@@ -25,27 +32,45 @@ pub struct DiskAddOnsFolder {
     pub repositories: Vec<DiskAddonRepository>,
     /// Error
     pub error: Option<String>,
+    /// Broken or foreign links and repositories without links
+    pub orphans: OrphanReport,
 }
 
 impl DiskAddOnsFolder {
     /// Scan the AddOns directory on disk
-    pub fn scan(path: &str) -> Result<Self, String> {
+    pub fn scan(path: &str) -> Result<Self, AddonError> {
+        Self::scan_with_cancel(path, &CancellationToken::default(), &StderrEvents)
+    }
+
+    /// Scan the AddOns directory on disk, skipping remaining remote fetches once cancelled.
+    /// Fetch retries and failures are reported to `events` as warnings of the repository.
+    pub fn scan_with_cancel(
+        path: &str,
+        cancel: &CancellationToken,
+        events: &dyn EventSink,
+    ) -> Result<Self, AddonError> {
+        let fetch = RemoteFetch::new(path, cancel, events);
         let addons_path = Path::new(path);
         let is_valid = crate::validate::is_valid_addons_folder_str(path);
         let manager_dir = addons_path.join(".addonmanager");
         let mut repositories = Vec::new();
         if manager_dir.exists() {
-            for repo_entry in std::fs::read_dir(&manager_dir)
-                .map_err(|e| format!("Failed to read manager dir {}: {e}", manager_dir.display()))?
-            {
-                let repo_path = repo_entry.map_err(|e| e.to_string())?.path();
-                if !repo_path.is_dir() {
+            for repo_entry in std::fs::read_dir(&manager_dir).map_err(|e| {
+                AddonError::io("Failed to read manager dir", &e).with_path(&manager_dir)
+            })? {
+                let repo_path = repo_entry
+                    .map_err(|e| {
+                        AddonError::io("Failed to read manager dir", &e).with_path(&manager_dir)
+                    })?
+                    .path();
+                // Hidden directories such as `.trash` belong to the manager itself
+                if !repo_path.is_dir() || is_hidden(&repo_path) {
                     continue;
                 }
                 let git_dir = repo_path.join(".git");
                 let mut disk_repo = if git_dir.exists() && git_dir.is_dir() {
                     // Git repository
-                    create_disk_addon_repository(&repo_path)?
+                    create_disk_addon_repository_inner(&repo_path, Some(&fetch))?
                 } else {
                     // Non-git folder
                     println!("Found non-git folder: {}", repo_path.display());
@@ -61,21 +86,27 @@ impl DiskAddOnsFolder {
             is_valid,
             repositories,
             error: None,
+            orphans: orphans::scan_orphans(addons_path),
         })
     }
 
     /// Scan the AddOns directory on disk (disk-only, no remote operations)
-    pub fn scan_disk_only(path: &str) -> Result<Self, String> {
+    pub fn scan_disk_only(path: &str) -> Result<Self, AddonError> {
         let addons_path = Path::new(path);
         let is_valid = crate::validate::is_valid_addons_folder_str(path);
         let manager_dir = addons_path.join(".addonmanager");
         let mut repositories = Vec::new();
         if manager_dir.exists() {
-            for repo_entry in std::fs::read_dir(&manager_dir)
-                .map_err(|e| format!("Failed to read manager dir {}: {e}", manager_dir.display()))?
-            {
-                let repo_path = repo_entry.map_err(|e| e.to_string())?.path();
-                if !repo_path.is_dir() {
+            for repo_entry in std::fs::read_dir(&manager_dir).map_err(|e| {
+                AddonError::io("Failed to read manager dir", &e).with_path(&manager_dir)
+            })? {
+                let repo_path = repo_entry
+                    .map_err(|e| {
+                        AddonError::io("Failed to read manager dir", &e).with_path(&manager_dir)
+                    })?
+                    .path();
+                // Hidden directories such as `.trash` belong to the manager itself
+                if !repo_path.is_dir() || is_hidden(&repo_path) {
                     continue;
                 }
                 let git_dir = repo_path.join(".git");
@@ -96,6 +127,7 @@ impl DiskAddOnsFolder {
             is_valid,
             repositories,
             error: None,
+            orphans: orphans::scan_orphans(addons_path),
         })
     }
 
@@ -106,6 +138,7 @@ impl DiskAddOnsFolder {
             is_valid: crate::validate::is_valid_addons_folder_str(path),
             repositories: Vec::new(),
             error: Some(error),
+            orphans: OrphanReport::default(),
         }
     }
 }
@@ -169,35 +202,111 @@ impl DiskAddonRepository {
     }
 }
 
-/// Check if addons are symlinked in the AddOns directory
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Check if addons are linked or deployed in the AddOns directory
 /// Updates the is_symlinked field for each addon
 pub fn check_addon_symlinks(addons: &mut [DiskAddon], addons_dir: &Path) {
     for addon in addons {
-        let path = addons_dir.join(&addon.name);
-        addon.is_symlinked = symlink::is_addon_symlinked(path);
+        addon.is_symlinked = deploy::is_linked(addons_dir, &addon.name);
     }
 }
 
 /// Create a DiskAddonRepository from a repository path
 /// This is used by both scan and install operations
-pub fn create_disk_addon_repository(repo_path: &Path) -> Result<DiskAddonRepository, String> {
-    create_disk_addon_repository_inner(repo_path, false)
+pub fn create_disk_addon_repository(repo_path: &Path) -> Result<DiskAddonRepository, AddonError> {
+    let folder_path = repo_path
+        .parent()
+        .and_then(Path::parent)
+        .unwrap_or(repo_path)
+        .to_string_lossy();
+    let cancel = CancellationToken::default();
+    let fetch = RemoteFetch::new(&folder_path, &cancel, &StderrEvents);
+    create_disk_addon_repository_inner(repo_path, Some(&fetch))
 }
 
 /// Create a DiskAddonRepository from a repository path (disk-only, no remote operations)
 /// This is used for fast disk-only scans
 pub fn create_disk_addon_repository_disk_only(
     repo_path: &Path,
-) -> Result<DiskAddonRepository, String> {
-    create_disk_addon_repository_inner(repo_path, true)
+) -> Result<DiskAddonRepository, AddonError> {
+    create_disk_addon_repository_inner(repo_path, None)
+}
+
+/// Remote fetches of a scan, repositories are fetched one after another
+struct RemoteFetch<'a> {
+    folder_path: &'a str,
+    cancel: &'a CancellationToken,
+    events: &'a dyn EventSink,
+    /// A fetch failed without reaching the server; the remaining ones get a single attempt
+    offline: Cell<bool>,
+}
+
+impl<'a> RemoteFetch<'a> {
+    fn new(folder_path: &'a str, cancel: &'a CancellationToken, events: &'a dyn EventSink) -> Self {
+        RemoteFetch {
+            folder_path,
+            cancel,
+            events,
+            offline: Cell::new(false),
+        }
+    }
+
+    fn warn(&self, repo_url: &str, message: String) {
+        let key = OperationKey {
+            repo_url: repo_url.to_string(),
+            folder_path: self.folder_path.to_string(),
+        };
+        if let Err(e) = emit_operation_event(self.events, &key, OperationEvent::Warning(message)) {
+            eprintln!("Failed to emit operation-event: {e}");
+        }
+    }
+
+    /// Fetch `branch` from origin, retrying server errors once
+    fn fetch(&self, remote: &mut git2::Remote, repo_url: &str, branch: &str) {
+        let policy = if self.offline.get() {
+            RetryPolicy::once()
+        } else {
+            RetryPolicy::scan()
+        };
+        let result = retry::retry_transient(
+            &policy,
+            || {
+                let mut callbacks = git2::RemoteCallbacks::new();
+                callbacks.transfer_progress(|_| !self.cancel.is_cancelled());
+                let mut fetch_options = git2::FetchOptions::new();
+                fetch_options.remote_callbacks(callbacks);
+                remote.fetch(&[branch], Some(&mut fetch_options), None)
+            },
+            |retry| {
+                if !retry::is_http_error(retry.error) {
+                    return false;
+                }
+                self.warn(repo_url, retry.message("Fetch"));
+                !self.cancel.is_cancelled()
+            },
+        );
+        if let Err(e) = result {
+            if e.class() == git2::ErrorClass::Net && !retry::is_http_error(&e) {
+                self.offline.set(true);
+            }
+            self.warn(
+                repo_url,
+                format!("Failed to fetch from remote: {}", e.message()),
+            );
+        }
+    }
 }
 
 fn create_disk_addon_repository_inner(
     repo_path: &Path,
-    disk_only: bool,
-) -> Result<DiskAddonRepository, String> {
+    fetch: Option<&RemoteFetch>,
+) -> Result<DiskAddonRepository, AddonError> {
     let repo = git2::Repository::open(repo_path)
-        .map_err(|e| format!("Failed to open git repo {}: {e}", repo_path.display()))?;
+        .map_err(|e| AddonError::git("Failed to open git repo", &e).with_path(repo_path))?;
 
     let repo_url = repo
         .find_remote("origin")
@@ -220,32 +329,25 @@ fn create_disk_addon_repository_inner(
     let (owner, _) = git::extract_owner_repo_from_url(&repo_url)
         .unwrap_or_else(|_| ("Unknown owner".to_string(), "Unknown repo".to_string()));
 
-    let latest_ref = if disk_only {
-        None
-    } else {
+    let latest_ref = match fetch {
+        None => None,
         // Fetch from remote to get latest ref
-        current_branch.as_ref().and_then(|branch| {
-            if let Ok(mut remote) = repo.find_remote("origin") {
-                let mut fetch_options = git2::FetchOptions::new();
+        Some(fetch) => current_branch.as_ref().and_then(|branch| {
+            if !fetch.cancel.is_cancelled()
+                && let Ok(mut remote) = repo.find_remote("origin")
+            {
                 let branch_name = branch.strip_prefix("origin/").unwrap_or(branch);
-
-                if let Err(e) = remote.fetch(&[branch_name], Some(&mut fetch_options), None) {
-                    eprintln!(
-                        "Warning: Failed to fetch from remote for {repo_path_display}: {e}",
-                        repo_path_display = repo_path.display()
-                    );
-                }
+                fetch.fetch(&mut remote, &repo_url, branch_name);
             }
 
             let refname = format!("refs/remotes/origin/{branch}");
             repo.find_reference(&refname)
                 .ok()
                 .and_then(|r| r.target().map(|oid| oid.to_string()))
-        })
+        }),
     };
 
-    let addons = find_all_sub_addons(&repo_path.to_path_buf())
-        .map_err(|e| format!("Failed to discover sub-addons: {e}"))?;
+    let addons = find_all_sub_addons(&repo_path.to_path_buf())?;
 
     let readme = find_readme(repo_path);
 
@@ -270,14 +372,15 @@ fn create_disk_addon_repository_inner(
 }
 
 /// Create a DiskAddonRepository from a non-git local folder
-pub fn create_non_git_addon_repository(folder_path: &Path) -> Result<DiskAddonRepository, String> {
+pub fn create_non_git_addon_repository(
+    folder_path: &Path,
+) -> Result<DiskAddonRepository, AddonError> {
     let folder_name = folder_path
         .file_name()
         .map(|f| f.to_string_lossy().to_string())
         .unwrap_or_else(|| "Unknown".to_string());
 
-    let addons = find_all_sub_addons(&folder_path.to_path_buf())
-        .map_err(|e| format!("Failed to discover sub-addons: {e}"))?;
+    let addons = find_all_sub_addons(&folder_path.to_path_buf())?;
 
     Ok(DiskAddonRepository {
         source: DiskAddonSource::Local {
@@ -334,7 +437,7 @@ fn get_branch_names(repo: &git2::Repository) -> Vec<String> {
 }
 
 /// Finds all sub-addons by searching for .toc files in the root directory and immediate subdirectories only
-pub fn find_all_sub_addons(path: &PathBuf) -> Result<Vec<DiskAddon>, String> {
+pub fn find_all_sub_addons(path: &PathBuf) -> Result<Vec<DiskAddon>, AddonError> {
     // Helper to extract notes from a .toc file
     fn extract_notes(toc_path: &std::path::Path) -> Option<String> {
         if let Ok(content) = std::fs::read_to_string(toc_path) {
@@ -351,9 +454,9 @@ pub fn find_all_sub_addons(path: &PathBuf) -> Result<Vec<DiskAddon>, String> {
     }
 
     // Helper to process a directory and collect .toc files
-    fn collect_toc_files(dir: &Path) -> Result<Vec<String>, String> {
+    fn collect_toc_files(dir: &Path) -> Result<Vec<String>, AddonError> {
         let toc_files = std::fs::read_dir(dir)
-            .map_err(|e| format!("Failed to read dir: {e}"))?
+            .map_err(|e| AddonError::io("Failed to discover sub-addons", &e).with_path(dir))?
             .filter_map(|entry| {
                 let path = entry.ok()?.path();
                 if path.is_file() && path.extension() == Some(OsStr::new("toc")) {
@@ -409,7 +512,7 @@ pub fn find_all_sub_addons(path: &PathBuf) -> Result<Vec<DiskAddon>, String> {
         // Search immediate subdirectories for addon folders
         sub_addons.extend(
             std::fs::read_dir(path)
-                .map_err(|e| format!("Failed to read repo dir: {e}"))?
+                .map_err(|e| AddonError::io("Failed to read repo dir", &e).with_path(path))?
                 .filter_map(Result::ok)
                 .map(|entry| entry.path())
                 .filter(|sub_path| sub_path.is_dir())
@@ -669,6 +772,51 @@ mod tests {
             sub_addons
         );
     }
+    #[derive(Default)]
+    struct RecordedWarnings(std::sync::Mutex<Vec<OperationKey>>);
+
+    impl EventSink for RecordedWarnings {
+        fn operation_event(
+            &self,
+            payload: crate::operation_reporter::OperationEventPayload,
+        ) -> Result<(), String> {
+            if let OperationEvent::Warning(message) = payload.event {
+                assert!(!message.contains("retrying"), "Retried: {message}");
+                self.0.lock().unwrap().push(payload.key);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_scan_does_not_retry_unreachable_remote() {
+        let (_temp, addons_dir) = crate::test_utils::setup_addons_dir();
+        for name in ["RepoA", "RepoB"] {
+            let repo_dir = addons_dir.join(".addonmanager").join(name);
+            std::fs::create_dir_all(&repo_dir).unwrap();
+            let repo = git2::Repository::init(&repo_dir).unwrap();
+            let signature = git2::Signature::now("test", "test@example.com").unwrap();
+            let tree = repo
+                .find_tree(repo.index().unwrap().write_tree().unwrap())
+                .unwrap();
+            repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+                .unwrap();
+            // Nothing listens on port 1, the connection is refused at once
+            repo.remote("origin", &format!("http://127.0.0.1:1/{name}.git"))
+                .unwrap();
+        }
+
+        let path = addons_dir.to_string_lossy().to_string();
+        let events = RecordedWarnings::default();
+        let folder =
+            DiskAddOnsFolder::scan_with_cancel(&path, &CancellationToken::default(), &events)
+                .unwrap();
+        assert_eq!(folder.repositories.len(), 2);
+        let warnings = events.0.lock().unwrap();
+        println!("{warnings:?}");
+        assert_eq!(warnings.len(), 2, "One failed fetch per repository");
+        assert!(warnings.iter().all(|key| key.folder_path == path));
+    }
 }
 
 /// Returns the canonical base name for a .toc file
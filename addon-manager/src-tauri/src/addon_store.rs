@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
@@ -7,82 +9,294 @@ use tauri::AppHandle;
 use tauri::Emitter;
 use tauri_plugin_store::StoreExt;
 
-use crate::operation_reporter::{OperationEvent, OperationEventPayload, OperationKey};
+use crate::deploy::LinkStrategy;
+use crate::error::{AddonError, AddonErrorKind};
+use crate::game_discovery;
+use crate::operation_reporter::{OperationEvent, OperationKey, emit_operation_event};
+use crate::repair;
+use crate::symlink::LinkStyle;
+use crate::update;
 
 const STORE_FILE: &str = "addon-manager.json";
 const STORE_KEY: &str = "addon-directories";
+/// Identifier from `tauri.conf.json`, which names the app data directory
+const APP_IDENTIFIER: &str = "io.github.sogladev.addon-manager";
 
-pub fn load_user_config(app: &AppHandle) -> Result<AddOnsUserConfig, String> {
-    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
-    let raw = store.get(STORE_KEY).unwrap_or_default();
-    let config: AddOnsUserConfig = serde_json::from_value(raw).unwrap_or_default();
-    Ok(config)
+/// Where the user config is persisted. The app goes through `tauri_plugin_store`,
+/// the command line reads and writes the same file with `FileConfigStore`.
+pub trait ConfigStore {
+    fn load(&self) -> Result<AddOnsUserConfig, AddonError>;
+    fn save(&self, config: &AddOnsUserConfig) -> Result<(), AddonError>;
 }
 
-#[tauri::command]
-pub async fn add_addon_directory(path: String, app_handle: AppHandle) -> Result<(), String> {
-    println!("Adding addon directory: {path}");
+fn config_err(message: &str, cause: impl ToString) -> AddonError {
+    AddonError::new(AddonErrorKind::Config, message.to_string()).with_cause(cause.to_string())
+}
 
-    // Check if directory exists first
-    if !std::path::Path::new(&path).exists() {
-        let error_msg = format!("Directory does not exist: {path}");
+impl ConfigStore for AppHandle {
+    fn load(&self) -> Result<AddOnsUserConfig, AddonError> {
+        let store = self
+            .store(STORE_FILE)
+            .map_err(|e| config_err("Failed to open config store", e))?;
+        let raw = store.get(STORE_KEY).unwrap_or_default();
+        Ok(serde_json::from_value(raw).unwrap_or_default())
+    }
 
-        // Emit operation error event to frontend
-        let operation_key = OperationKey {
-            repo_url: String::new(), // No repo URL for directory operations
-            folder_path: path.clone(),
-        };
+    fn save(&self, config: &AddOnsUserConfig) -> Result<(), AddonError> {
+        let store = self
+            .store(STORE_FILE)
+            .map_err(|e| config_err("Failed to open config store", e))?;
+        let value = serde_json::to_value(config)
+            .map_err(|e| config_err("Failed to serialize config", e))?;
+        store.set(STORE_KEY, value);
+        store
+            .save()
+            .map_err(|e| config_err("Failed to save config store", e))
+    }
+}
 
-        app_handle
-            .emit(
-                "operation-event",
-                OperationEventPayload {
-                    key: operation_key,
-                    event: OperationEvent::Error(error_msg.clone()),
-                },
-            )
-            .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+/// The store file of the app, read and written without a running Tauri app
+pub struct FileConfigStore {
+    path: PathBuf,
+}
 
-        return Err(error_msg);
+impl FileConfigStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileConfigStore { path: path.into() }
     }
 
-    let store = app_handle.store(STORE_FILE).map_err(|e| e.to_string())?;
-    let raw = store.get(STORE_KEY).unwrap_or_default();
-    let mut config: AddOnsUserConfig = serde_json::from_value(raw).unwrap_or_default();
+    /// `addon-manager.json` in the app data directory, where the app keeps it
+    pub fn default_location() -> Option<Self> {
+        app_data_dir().map(|dir| Self::new(dir.join(STORE_FILE)))
+    }
 
-    if !config.folders.iter().any(|f| f.path == path) {
-        config.folders.push(AddOnsFolderUserMeta {
-            path: path.clone(),
-            repos: Vec::new(),
-        });
-        let value = serde_json::to_value(&config).map_err(|e| e.to_string())?;
-        store.set(STORE_KEY, value);
-        store.save().map_err(|e| e.to_string())?;
-        app_handle
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All keys of the store file; other keys than ours are kept when saving
+    fn read_entries(&self) -> Result<serde_json::Map<String, serde_json::Value>, AddonError> {
+        match fs::read(&self.path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| config_err("Failed to parse config store", e).with_path(&self.path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(serde_json::Map::new()),
+            Err(e) => Err(AddonError::io("Failed to read config store", &e).with_path(&self.path)),
+        }
+    }
+}
+
+impl ConfigStore for FileConfigStore {
+    fn load(&self) -> Result<AddOnsUserConfig, AddonError> {
+        let raw = self.read_entries()?.remove(STORE_KEY).unwrap_or_default();
+        Ok(serde_json::from_value(raw).unwrap_or_default())
+    }
+
+    fn save(&self, config: &AddOnsUserConfig) -> Result<(), AddonError> {
+        let mut entries = self.read_entries()?;
+        let value = serde_json::to_value(config)
+            .map_err(|e| config_err("Failed to serialize config", e))?;
+        entries.insert(STORE_KEY.to_string(), value);
+        let bytes = serde_json::to_vec_pretty(&entries)
+            .map_err(|e| config_err("Failed to serialize config", e))?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                AddonError::io("Failed to create config directory", &e).with_path(parent)
+            })?;
+        }
+        // Write next to the store and rename, so the app never reads a partial file
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, bytes)
+            .and_then(|_| fs::rename(&tmp, &self.path))
+            .map_err(|e| AddonError::io("Failed to write config store", &e).with_path(&self.path))
+    }
+}
+
+/// The directory Tauri resolves `BaseDirectory::AppData` to
+pub fn app_data_dir() -> Option<PathBuf> {
+    let home = || std::env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".local/share")))
+    };
+    base.map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+pub fn load_user_config(store: &impl ConfigStore) -> Result<AddOnsUserConfig, AddonError> {
+    store.load()
+}
+
+pub fn save_user_config(
+    store: &impl ConfigStore,
+    config: &AddOnsUserConfig,
+) -> Result<(), AddonError> {
+    store.save(config)
+}
+
+/// Persist whether a sub-addon is linked, so `repair::repair_folder` can restore it
+pub fn set_addon_enabled(
+    store: &impl ConfigStore,
+    folder_path: &str,
+    repo_url: &str,
+    addon_name: &str,
+    enabled: bool,
+) -> Result<(), AddonError> {
+    set_addons_enabled(
+        store,
+        folder_path,
+        repo_url,
+        &[addon_name.to_string()],
+        enabled,
+    )
+}
+
+/// Persist the linked state of several sub-addons of a repository at once, e.g.
+/// every sub-addon linked by an install
+pub fn set_addons_enabled(
+    store: &impl ConfigStore,
+    folder_path: &str,
+    repo_url: &str,
+    addon_names: &[String],
+    enabled: bool,
+) -> Result<(), AddonError> {
+    let mut config = load_user_config(store)?;
+
+    let Some(repo) = config.repo_meta_mut(folder_path, repo_url) else {
+        return Ok(());
+    };
+    for addon_name in addon_names {
+        repo.addons
+            .entry(addon_name.clone())
+            .and_modify(|meta| meta.enabled = enabled)
+            .or_insert(AddonUserMeta {
+                enabled,
+                name: None,
+            });
+    }
+
+    save_user_config(store, &config)
+}
+
+/// Change the metadata of a repository in a configured folder
+pub fn update_repo_meta(
+    store: &impl ConfigStore,
+    folder_path: &str,
+    repo_url: &str,
+    change: impl FnOnce(&mut AddonRepositoryUserMeta),
+) -> Result<(), AddonError> {
+    let mut config = load_user_config(store)?;
+    let repo = config.repo_meta_mut(folder_path, repo_url).ok_or_else(|| {
+        AddonError::new(AddonErrorKind::NotFound, "Folder is not configured").with_path(folder_path)
+    })?;
+    change(repo);
+    save_user_config(store, &config)
+}
+
+/// Add an existing directory to the managed folders.
+/// Returns false when it was already managed.
+pub fn add_folder(store: &impl ConfigStore, path: &str) -> Result<bool, AddonError> {
+    if !Path::new(path).exists() {
+        return Err(
+            AddonError::new(AddonErrorKind::NotFound, "Directory does not exist").with_path(path),
+        );
+    }
+    let mut config = load_user_config(store)?;
+    if config.folders.iter().any(|f| f.path == path) {
+        return Ok(false);
+    }
+    config.folders.push(AddOnsFolderUserMeta {
+        path: path.to_string(),
+        repos: Vec::new(),
+        settings: FolderSettings::default(),
+    });
+    save_user_config(store, &config)?;
+    Ok(true)
+}
+
+/// Add an AddOns folder, or the root of a game installation whose `Interface/AddOns`
+/// is created when missing. Returns the managed path and whether it was added.
+pub fn add_game_folder<F>(
+    store: &impl ConfigStore,
+    path: &str,
+    reporter: F,
+) -> Result<(String, bool), AddonError>
+where
+    F: FnMut(OperationEvent),
+{
+    let addons_dir = game_discovery::ensure_addons_folder(Path::new(path), reporter)?;
+    let addons_path = addons_dir.to_string_lossy().to_string();
+    let added = add_folder(store, &addons_path)?;
+    if added && let Some(version) = game_discovery::detect_client_version(&addons_dir) {
+        let mut config = load_user_config(store)?;
+        if let Some(folder) = config.folders.iter_mut().find(|f| f.path == addons_path) {
+            folder.settings.client_version = Some(version);
+        }
+        save_user_config(store, &config)?;
+    }
+    Ok((addons_path, added))
+}
+
+/// Stop managing a folder; its files are left alone.
+/// Returns false when it was not managed.
+pub fn remove_folder(store: &impl ConfigStore, path: &str) -> Result<bool, AddonError> {
+    let mut config = load_user_config(store)?;
+    let orig_len = config.folders.len();
+    config.folders.retain(|f| f.path != path);
+    if config.folders.len() == orig_len {
+        return Ok(false);
+    }
+    save_user_config(store, &config)?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn add_addon_directory(path: String, app_handle: AppHandle) -> Result<(), String> {
+    println!("Adding addon directory: {path}");
+
+    let operation_key = OperationKey {
+        repo_url: String::new(), // No repo URL for directory operations
+        folder_path: path.clone(),
+    };
+    let reporter = |event| {
+        if let Err(e) = emit_operation_event(&app_handle, &operation_key, event) {
+            eprintln!("Failed to emit operation-event: {e}");
+        }
+    };
+
+    match add_game_folder(&app_handle, &path, reporter) {
+        Ok((_, true)) => app_handle
             .emit("addon-data-updated", ())
-            .map_err(|e| format!("Failed to emit event: {e}"))?;
+            .map_err(|e| format!("Failed to emit event: {e}")),
+        Ok((_, false)) => Ok(()),
+        Err(error) => {
+            // Emit operation error event to frontend
+            emit_operation_event(
+                &app_handle,
+                &operation_key,
+                OperationEvent::Error(error.clone()),
+            )
+            .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+
+            Err(error.into())
+        }
     }
-    Ok(())
 }
 
 #[tauri::command]
 pub async fn delete_addon_directory(path: String, app: AppHandle) -> Result<(), String> {
     println!("Deleting addon directory: {path}");
-    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
-    let raw = store.get(STORE_KEY).unwrap_or_default();
-    let mut config: AddOnsUserConfig = serde_json::from_value(raw).unwrap_or_default();
-
-    let orig_len = config.folders.len();
-    config.folders.retain(|f| f.path != path);
-    if config.folders.len() != orig_len {
-        let value = serde_json::to_value(&config).map_err(|e| e.to_string())?;
-        store.set(STORE_KEY, value);
-        store.save().map_err(|e| e.to_string())?;
+    if remove_folder(&app, &path)? {
         app.emit("addon-data-updated", ())
             .map_err(|e| format!("Failed to emit event: {e}"))?;
     }
     Ok(())
 }
+
 #[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export)]
@@ -96,7 +310,13 @@ pub struct AddonUserMeta {
 #[ts(export)]
 pub struct AddonRepositoryUserMeta {
     pub repo_url: String,                       // used as unique key
-    pub addons: HashMap<String, AddonUserMeta>, // keyed by repo_url
+    pub addons: HashMap<String, AddonUserMeta>, // keyed by sub-addon name
+    /// Skipped by "update all" and headless updates, it can still be updated on its own
+    #[serde(default)]
+    pub exclude_from_bulk_update: bool,
+    /// Updated by scheduled update checks without asking
+    #[serde(default)]
+    pub auto_update: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, TS)]
@@ -107,6 +327,42 @@ pub struct AddOnsFolderUserMeta {
     /// Absolute path to the AddOns directory
     pub path: String,
     pub repos: Vec<AddonRepositoryUserMeta>,
+    #[serde(flatten)]
+    pub settings: FolderSettings,
+}
+
+/// Which repositories of a folder scheduled update checks update without asking
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS, PartialEq, Eq, Default)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum AutoUpdatePolicy {
+    /// Repositories flagged auto-update
+    #[default]
+    PerRepository,
+    /// Every repository
+    All,
+    /// None, even when flagged
+    Never,
+}
+
+/// Settings of a single AddOns directory, all optional
+#[derive(Debug, Serialize, Deserialize, Clone, TS, PartialEq, Eq, Default)]
+#[ts(export)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderSettings {
+    /// Shown instead of the path
+    pub display_name: Option<String>,
+    /// Game flavor or client version, e.g. `3.3.5.12340`
+    pub client_version: Option<String>,
+    /// How sub-addons are placed into this folder; symlinks when unset.
+    /// Applies to addons linked after it changed.
+    pub link_strategy: Option<LinkStrategy>,
+    pub auto_update: Option<AutoUpdatePolicy>,
+    /// Branch checked out on install when the repository has it, e.g. a realm
+    /// specific branch; the remote default otherwise
+    pub preferred_branch: Option<String>,
+    /// Repository URL globs skipped by bulk updates in this folder
+    pub excluded_repos: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, TS)]
@@ -119,16 +375,189 @@ pub struct AddOnsUserConfig {
     pub folders: Vec<AddOnsFolderUserMeta>,
     /// Selected theme name
     pub theme: Option<String>,
+    /// Days deleted addons stay in the trash before being purged
+    pub trash_retention_days: Option<u32>,
+    /// SavedVariables snapshots kept per repository before updates
+    pub saved_variables_backup_count: Option<u32>,
+    /// Whether new links are absolute or relative to the AddOns folder
+    pub link_style: Option<LinkStyle>,
+    /// Minutes between background update checks; disabled when unset or 0
+    pub update_check_interval_minutes: Option<u32>,
+}
+
+impl AddOnsUserConfig {
+    /// Metadata of a repository in a configured folder, created when missing
+    fn repo_meta_mut(
+        &mut self,
+        folder_path: &str,
+        repo_url: &str,
+    ) -> Option<&mut AddonRepositoryUserMeta> {
+        let folder = self.folders.iter_mut().find(|f| f.path == folder_path)?;
+        let index = match folder.repos.iter().position(|r| r.repo_url == repo_url) {
+            Some(index) => index,
+            None => {
+                folder.repos.push(AddonRepositoryUserMeta {
+                    repo_url: repo_url.to_string(),
+                    addons: HashMap::new(),
+                    exclude_from_bulk_update: false,
+                    auto_update: false,
+                });
+                folder.repos.len() - 1
+            }
+        };
+        folder.repos.get_mut(index)
+    }
+
+    fn repo_meta(&self, folder_path: &str, repo_url: &str) -> Option<&AddonRepositoryUserMeta> {
+        self.folders
+            .iter()
+            .find(|f| f.path == folder_path)?
+            .repos
+            .iter()
+            .find(|r| r.repo_url == repo_url)
+    }
+
+    /// Settings of a folder, the defaults when it is not configured
+    pub fn folder_settings(&self, folder_path: &str) -> FolderSettings {
+        self.folders
+            .iter()
+            .find(|f| f.path == folder_path)
+            .map(|f| f.settings.clone())
+            .unwrap_or_default()
+    }
+
+    /// Excluded by the flag of the repository or the excluded repos of its folder
+    pub fn is_excluded_from_bulk_update(&self, folder_path: &str, repo_url: &str) -> bool {
+        self.repo_meta(folder_path, repo_url)
+            .is_some_and(|r| r.exclude_from_bulk_update)
+            || self
+                .folder_settings(folder_path)
+                .excluded_repos
+                .iter()
+                .any(|glob| update::glob_matches(glob, repo_url))
+    }
+
+    pub fn is_auto_update(&self, folder_path: &str, repo_url: &str) -> bool {
+        match self
+            .folder_settings(folder_path)
+            .auto_update
+            .unwrap_or_default()
+        {
+            AutoUpdatePolicy::PerRepository => self
+                .repo_meta(folder_path, repo_url)
+                .is_some_and(|r| r.auto_update),
+            AutoUpdatePolicy::All => true,
+            AutoUpdatePolicy::Never => false,
+        }
+    }
 }
 
 #[tauri::command]
 pub async fn save_theme(theme: String, app_handle: AppHandle) -> Result<(), String> {
     let mut config = load_user_config(&app_handle)?;
     config.theme = Some(theme.clone());
-    let store = app_handle.store(STORE_FILE).map_err(|e| e.to_string())?;
-    let value = serde_json::to_value(&config).map_err(|e| e.to_string())?;
-    store.set(STORE_KEY, value);
-    store.save().map_err(|e| e.to_string())
+    save_user_config(&app_handle, &config).map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn save_trash_retention(days: u32, app_handle: AppHandle) -> Result<(), String> {
+    let mut config = load_user_config(&app_handle)?;
+    config.trash_retention_days = Some(days);
+    save_user_config(&app_handle, &config).map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn save_backup_count(count: u32, app_handle: AppHandle) -> Result<(), String> {
+    let mut config = load_user_config(&app_handle)?;
+    config.saved_variables_backup_count = Some(count);
+    save_user_config(&app_handle, &config).map_err(Into::into)
+}
+
+/// Exclude a repository from "update all" and headless updates, or include it again
+#[tauri::command]
+pub async fn save_bulk_update_excluded(
+    path: String,
+    repo_url: String,
+    excluded: bool,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    update_repo_meta(&app_handle, &path, &repo_url, |repo| {
+        repo.exclude_from_bulk_update = excluded
+    })?;
+    app_handle
+        .emit("addon-data-updated", ())
+        .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))
+}
+
+/// Let scheduled update checks update a repository without asking
+#[tauri::command]
+pub async fn save_repo_auto_update(
+    path: String,
+    repo_url: String,
+    enabled: bool,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    update_repo_meta(&app_handle, &path, &repo_url, |repo| {
+        repo.auto_update = enabled
+    })?;
+    app_handle
+        .emit("addon-data-updated", ())
+        .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))
+}
+
+/// Save the minutes between background update checks, `None` or 0 disables them
+#[tauri::command]
+pub async fn save_update_check_interval(
+    minutes: Option<u32>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let mut config = load_user_config(&app_handle)?;
+    config.update_check_interval_minutes = minutes;
+    save_user_config(&app_handle, &config).map_err(Into::into)
+}
+
+/// Replace the settings of a folder
+#[tauri::command]
+pub async fn update_folder_settings(
+    path: String,
+    settings: FolderSettings,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let mut config = load_user_config(&app_handle)?;
+    let folder = config
+        .folders
+        .iter_mut()
+        .find(|f| f.path == path)
+        .ok_or_else(|| format!("Folder not found: {path}"))?;
+    folder.settings = settings;
+    save_user_config(&app_handle, &config)?;
+    app_handle
+        .emit("addon-data-updated", ())
+        .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))
+}
+
+/// Save the link style and convert the existing links of every folder to it.
+/// Returns the converted links.
+#[tauri::command]
+pub async fn save_link_style(
+    style: LinkStyle,
+    app_handle: AppHandle,
+) -> Result<Vec<repair::LinkChange>, String> {
+    let mut config = load_user_config(&app_handle)?;
+    config.link_style = Some(style);
+    save_user_config(&app_handle, &config)?;
+
+    let mut changes = Vec::new();
+    for folder in &config.folders {
+        match repair::migrate_links(std::path::Path::new(&folder.path), style) {
+            Ok(converted) => changes.extend(converted),
+            Err(e) => eprintln!("Failed to convert links in {}: {e}", folder.path),
+        }
+    }
+    app_handle
+        .emit("addon-data-updated", ())
+        .map_err(|e| format!("Failed to emit event: {e}"))?;
+    Ok(changes)
 }
 
 #[tauri::command]
@@ -136,3 +565,58 @@ pub async fn load_theme(app_handle: AppHandle) -> Result<String, String> {
     let config = load_user_config(&app_handle)?;
     Ok(config.theme.clone().unwrap_or_default())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_config_store_keeps_other_keys() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = FileConfigStore::new(temp.path().join("data/addon-manager.json"));
+        assert!(store.load().unwrap().folders.is_empty());
+
+        fs::create_dir_all(temp.path().join("data")).unwrap();
+        fs::write(store.path(), r#"{"window-state": {"width": 800}}"#).unwrap();
+        let mut config = store.load().unwrap();
+        config.theme = Some("dark".to_string());
+        store.save(&config).unwrap();
+
+        let raw: serde_json::Value =
+            serde_json::from_slice(&fs::read(store.path()).unwrap()).unwrap();
+        println!("{raw:#}");
+        assert_eq!(raw["window-state"]["width"], 800);
+        assert_eq!(raw[STORE_KEY]["theme"], "dark");
+        assert_eq!(store.load().unwrap().theme.as_deref(), Some("dark"));
+    }
+
+    #[test]
+    fn test_folder_settings_from_older_config() {
+        // Written before folder settings existed, only the link strategy was stored
+        let raw = r#"{"folders": [{"path": "/wow/Interface/AddOns", "repos": [
+            {"repoUrl": "https://github.com/owner/A", "addons": {}, "autoUpdate": true},
+            {"repoUrl": "https://github.com/owner/B", "addons": {}}
+        ], "linkStrategy": "copy"}]}"#;
+        let mut config: AddOnsUserConfig = serde_json::from_str(raw).unwrap();
+        let path = "/wow/Interface/AddOns";
+        let (a, b) = ("https://github.com/owner/A", "https://github.com/owner/B");
+        assert_eq!(
+            config.folder_settings(path).link_strategy,
+            Some(LinkStrategy::Copy)
+        );
+        assert!(config.is_auto_update(path, a) && !config.is_auto_update(path, b));
+        assert!(!config.is_excluded_from_bulk_update(path, b));
+
+        let settings = &mut config.folders[0].settings;
+        settings.auto_update = Some(AutoUpdatePolicy::Never);
+        settings.excluded_repos = vec!["*/owner/B".to_string()];
+        assert!(!config.is_auto_update(path, a));
+        assert!(config.is_excluded_from_bulk_update(path, b));
+        assert!(!config.is_excluded_from_bulk_update(path, a));
+
+        let saved = serde_json::to_value(&config).unwrap();
+        println!("{saved:#}");
+        assert_eq!(saved["folders"][0]["linkStrategy"], "copy");
+        assert_eq!(saved["folders"][0]["autoUpdate"], "never");
+    }
+}
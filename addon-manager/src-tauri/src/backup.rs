@@ -0,0 +1,316 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use git2::{Oid, Repository, ResetType};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+use crate::{
+    addon_disk,
+    addon_store::{self, ConfigStore},
+    deploy,
+    error::{AddonError, AddonErrorKind},
+    game_process,
+    lock::OperationLock,
+    remove, saved_variables, validate,
+};
+
+/// Directory in `.addonmanager` holding SavedVariables snapshots per repository
+const BACKUP_DIR: &str = ".backups";
+const MANIFEST_FILE: &str = "backup.json";
+/// Snapshots kept per repository, unless configured otherwise
+pub const DEFAULT_BACKUP_COUNT: u32 = 5;
+
+/// A snapshot of a repository's saved variables, stored in
+/// `.addonmanager/.backups/<repo>/<id>`
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedVariablesBackup {
+    /// Timestamp based directory name
+    pub id: String,
+    pub repo_name: String,
+    #[ts(type = "string")]
+    pub created_at: DateTime<Utc>,
+    /// Repository HEAD when the snapshot was taken
+    pub repo_ref: Option<String>,
+    /// Files relative to the `WTF` directory
+    pub files: Vec<String>,
+}
+
+fn repo_backup_dir(addons_dir: &Path, repo_name: &str) -> PathBuf {
+    addons_dir
+        .join(".addonmanager")
+        .join(BACKUP_DIR)
+        .join(repo_name)
+}
+
+/// `SavedVariables/<Addon>.lua*` files of the repository's sub-addons
+fn find_backup_files(wtf: &Path, repo_dir: &Path) -> Vec<PathBuf> {
+    let Ok(addons) = addon_disk::find_all_sub_addons(&repo_dir.to_path_buf()) else {
+        return Vec::new();
+    };
+    let prefixes: Vec<String> = addons
+        .iter()
+        .map(|addon| format!("{}.lua", addon.name))
+        .collect();
+
+    let mut files: Vec<PathBuf> = saved_variables::saved_variables_dirs(wtf)
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            prefixes.iter().any(|prefix| name.starts_with(prefix))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Copy the repository's saved variables into a new snapshot and prune old ones,
+/// keeping the newest `keep`. Returns None when there is nothing to back up.
+pub fn backup_saved_variables(
+    addons_dir: &Path,
+    repo_name: &str,
+    keep: u32,
+) -> Result<Option<SavedVariablesBackup>, AddonError> {
+    let Some(wtf) = saved_variables::wtf_dir(addons_dir) else {
+        return Ok(None);
+    };
+    let repo_dir = addons_dir.join(".addonmanager").join(repo_name);
+    let files = find_backup_files(&wtf, &repo_dir);
+    if files.is_empty() || keep == 0 {
+        return Ok(None);
+    }
+
+    let created_at = Utc::now();
+    let backups = repo_backup_dir(addons_dir, repo_name);
+    let base_id = created_at.format("%Y%m%dT%H%M%S%.3fZ").to_string();
+    let mut id = base_id.clone();
+    let mut suffix = 1;
+    while backups.join(&id).exists() {
+        id = format!("{base_id}-{suffix}");
+        suffix += 1;
+    }
+    let snapshot = backups.join(&id);
+
+    let mut relative_files = Vec::new();
+    for file in &files {
+        let relative = file.strip_prefix(&wtf).unwrap_or(file);
+        let destination = snapshot.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| AddonError::io("Failed to create backup dir", &e).with_path(parent))?;
+        }
+        fs::copy(file, &destination)
+            .map_err(|e| AddonError::io("Failed to back up saved variables", &e).with_path(file))?;
+        relative_files.push(relative.to_string_lossy().to_string());
+    }
+
+    let backup = SavedVariablesBackup {
+        id,
+        repo_name: repo_name.to_string(),
+        created_at,
+        repo_ref: Repository::open(&repo_dir)
+            .ok()
+            .and_then(|repo| repo.head().ok()?.target())
+            .map(|oid| oid.to_string()),
+        files: relative_files,
+    };
+    let manifest = snapshot.join(MANIFEST_FILE);
+    let content = serde_json::to_string_pretty(&backup).map_err(|e| {
+        AddonError::new(AddonErrorKind::Other, "Failed to serialize backup").with_cause(e)
+    })?;
+    fs::write(&manifest, content)
+        .map_err(|e| AddonError::io("Failed to write backup manifest", &e).with_path(&manifest))?;
+
+    prune_backups(addons_dir, repo_name, keep);
+    Ok(Some(backup))
+}
+
+/// Snapshots of a repository's saved variables, newest first
+pub fn list_backups(addons_dir: &Path, repo_name: &str) -> Vec<SavedVariablesBackup> {
+    let Ok(entries) = fs::read_dir(repo_backup_dir(addons_dir, repo_name)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<SavedVariablesBackup> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let manifest = entry.path().join(MANIFEST_FILE);
+            let content = fs::read_to_string(&manifest).ok()?;
+            serde_json::from_str(&content)
+                .map_err(|e| eprintln!("Skipping unreadable backup {}: {e}", manifest.display()))
+                .ok()
+        })
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    backups
+}
+
+fn prune_backups(addons_dir: &Path, repo_name: &str, keep: u32) {
+    let backups_dir = repo_backup_dir(addons_dir, repo_name);
+    for old in list_backups(addons_dir, repo_name)
+        .into_iter()
+        .skip(keep as usize)
+    {
+        let dir = backups_dir.join(&old.id);
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            eprintln!("Failed to prune backup {}: {e}", dir.display());
+        }
+    }
+}
+
+/// Copy a snapshot's files back into `WTF`, overwriting the current ones.
+/// With `reset_repo`, the repository is also reset to the commit it was at
+/// when the snapshot was taken and its deployments are synced. Fails while the
+/// game is running, since it overwrites SavedVariables on exit.
+pub fn restore_backup(
+    addons_dir: &Path,
+    repo_name: &str,
+    backup_id: &str,
+    reset_repo: bool,
+) -> Result<SavedVariablesBackup, AddonError> {
+    let backup = list_backups(addons_dir, repo_name)
+        .into_iter()
+        .find(|backup| backup.id == backup_id)
+        .ok_or_else(|| {
+            AddonError::new(
+                AddonErrorKind::NotFound,
+                format!("No backup '{backup_id}' for {repo_name}"),
+            )
+        })?;
+    let wtf = saved_variables::wtf_dir(addons_dir).ok_or_else(|| {
+        AddonError::new(AddonErrorKind::NotFound, "WTF directory not found").with_path(addons_dir)
+    })?;
+    game_process::ensure_not_running(addons_dir, "restoring saved variables", &mut |_| {})?;
+    let _lock = OperationLock::acquire_repo(addons_dir, repo_name, "restore")?;
+
+    if reset_repo && let Some(repo_ref) = &backup.repo_ref {
+        let repo_dir = validate::ensure_manager_dir(addons_dir)?.join(repo_name);
+        let git_err =
+            |message: &str, e: git2::Error| AddonError::git(message, &e).with_path(&repo_dir);
+        let repo = Repository::open(&repo_dir).map_err(|e| git_err("Failed to open repo", e))?;
+        let oid = Oid::from_str(repo_ref).map_err(|e| git_err("Invalid backup ref", e))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| git_err("Backup commit not found", e))?;
+        repo.reset(commit.as_object(), ResetType::Hard, None)
+            .map_err(|e| git_err("Failed to reset repo", e))?;
+        // Copies and hardlink trees do not follow the working tree on their own
+        deploy::sync_repo_deployments(addons_dir, repo_name)?;
+    }
+
+    let snapshot = repo_backup_dir(addons_dir, repo_name).join(&backup.id);
+    for relative in &backup.files {
+        let source = snapshot.join(relative);
+        let destination = wtf.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                AddonError::io("Failed to create saved variables dir", &e).with_path(parent)
+            })?;
+        }
+        fs::copy(&source, &destination).map_err(|e| {
+            AddonError::io("Failed to restore saved variables", &e).with_path(&destination)
+        })?;
+    }
+    Ok(backup)
+}
+
+/// Snapshots kept per repository as configured by the user
+pub fn configured_backup_count(store: &impl ConfigStore) -> u32 {
+    addon_store::load_user_config(store)
+        .ok()
+        .and_then(|config| config.saved_variables_backup_count)
+        .unwrap_or(DEFAULT_BACKUP_COUNT)
+}
+
+#[tauri::command]
+pub async fn list_backups_cmd(
+    path: String,
+    repo_url: String,
+) -> Result<Vec<SavedVariablesBackup>, String> {
+    let repo_name = remove::repo_name_from_key(&repo_url)?;
+    Ok(list_backups(Path::new(&path), &repo_name))
+}
+
+#[tauri::command]
+pub async fn restore_backup_cmd(
+    app_handle: AppHandle,
+    path: String,
+    repo_url: String,
+    backup_id: String,
+    reset_repo: bool,
+) -> Result<(), String> {
+    let repo_name = remove::repo_name_from_key(&repo_url)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        restore_backup(Path::new(&path), &repo_name, &backup_id, reset_repo)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))??;
+
+    app_handle
+        .emit("addon-data-updated", ())
+        .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{print_dir_tree, setup_addons_dir};
+
+    fn setup(addons_dir: &Path) -> PathBuf {
+        let repo_dir = validate::ensure_manager_dir(addons_dir)
+            .unwrap()
+            .join("FakeRepo");
+        fs::create_dir_all(repo_dir.join("FakeAddon")).unwrap();
+        fs::write(repo_dir.join("FakeAddon").join("FakeAddon.toc"), "").unwrap();
+
+        let wtf = addons_dir.parent().unwrap().parent().unwrap().join("WTF");
+        let sv = wtf.join("Account/ACCOUNT/Realm/Char/SavedVariables");
+        fs::create_dir_all(&sv).unwrap();
+        fs::write(sv.join("FakeAddon.lua"), "v1").unwrap();
+        fs::write(sv.join("OtherAddon.lua"), "other").unwrap();
+        sv.join("FakeAddon.lua")
+    }
+
+    #[test]
+    fn test_backup_and_restore_saved_variables() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let file = setup(&addons_dir);
+
+        let backup = backup_saved_variables(&addons_dir, "FakeRepo", DEFAULT_BACKUP_COUNT)
+            .unwrap()
+            .expect("Expected a backup");
+        print_dir_tree(addons_dir.to_str().unwrap());
+        assert_eq!(
+            backup.files,
+            vec!["Account/ACCOUNT/Realm/Char/SavedVariables/FakeAddon.lua".to_string()]
+        );
+
+        fs::write(&file, "corrupted").unwrap();
+        restore_backup(&addons_dir, "FakeRepo", &backup.id, false).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "v1");
+    }
+
+    #[test]
+    fn test_backups_are_pruned() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        setup(&addons_dir);
+
+        for _ in 0..4 {
+            backup_saved_variables(&addons_dir, "FakeRepo", 2).unwrap();
+        }
+        let backups = list_backups(&addons_dir, "FakeRepo");
+        assert_eq!(backups.len(), 2, "Expected only the newest 2: {backups:?}");
+        assert!(backups[0].created_at >= backups[1].created_at);
+    }
+}
@@ -0,0 +1,349 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+use crate::{
+    addon_disk, deploy,
+    error::{AddonError, AddonErrorKind},
+    saved_variables,
+};
+
+/// Per character addon list written by the game client
+const ADDONS_FILE: &str = "AddOns.txt";
+/// Line ending used when the file has none to copy from, matching the client
+const DEFAULT_NEWLINE: &str = "\r\n";
+
+/// A character found in `WTF/Account/<account>/<realm>/<name>`
+#[derive(Debug, Serialize, Deserialize, Clone, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct Character {
+    pub account: String,
+    pub realm: String,
+    pub name: String,
+}
+
+/// A managed sub-addon and whether it is enabled for a character
+#[derive(Debug, Serialize, Clone, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct CharacterAddon {
+    pub name: String,
+    pub repo_name: String,
+    /// Addons missing from `AddOns.txt` are enabled by the client
+    pub enabled: bool,
+}
+
+/// `AddOns.txt` kept as raw lines so that writing it back only changes
+/// the value of the toggled entry
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct AddOnsTxt {
+    /// Lines including their line ending
+    lines: Vec<String>,
+}
+
+impl AddOnsTxt {
+    fn parse(content: &str) -> Self {
+        AddOnsTxt {
+            lines: content.split_inclusive('\n').map(str::to_string).collect(),
+        }
+    }
+
+    fn newline(&self) -> &str {
+        self.lines
+            .iter()
+            .find_map(|line| {
+                if line.ends_with("\r\n") {
+                    Some("\r\n")
+                } else if line.ends_with('\n') {
+                    Some("\n")
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(DEFAULT_NEWLINE)
+    }
+
+    /// `Name: enabled` split into (name, position of the value, value)
+    fn entry(line: &str) -> Option<(&str, usize, &str)> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (name, value) = line.split_once(':')?;
+        let start = name.len() + 1 + (value.len() - value.trim_start().len());
+        Some((name.trim(), start, value.trim()))
+    }
+
+    fn is_enabled(&self, addon_name: &str) -> Option<bool> {
+        self.lines.iter().find_map(|line| {
+            let (name, _, value) = Self::entry(line)?;
+            name.eq_ignore_ascii_case(addon_name)
+                .then(|| value.eq_ignore_ascii_case("enabled"))
+        })
+    }
+
+    fn set_enabled(&mut self, addon_name: &str, enabled: bool) {
+        let value = if enabled { "enabled" } else { "disabled" };
+        for line in &mut self.lines {
+            let Some((name, start, _)) = Self::entry(line) else {
+                continue;
+            };
+            if name.eq_ignore_ascii_case(addon_name) {
+                let ending = &line[line.trim_end_matches(['\r', '\n']).len()..];
+                *line = format!("{}{value}{ending}", &line[..start]);
+                return;
+            }
+        }
+
+        let newline = self.newline().to_string();
+        if let Some(last) = self.lines.last_mut()
+            && !last.ends_with('\n')
+        {
+            last.push_str(&newline);
+        }
+        self.lines.push(format!("{addon_name}: {value}{newline}"));
+    }
+
+    fn content(&self) -> String {
+        self.lines.concat()
+    }
+}
+
+/// Characters that have logged in at least once in the game installation
+/// the AddOns folder belongs to
+pub fn list_characters(addons_dir: &Path) -> Vec<Character> {
+    let Some(wtf) = saved_variables::wtf_dir(addons_dir) else {
+        return Vec::new();
+    };
+    let name = |path: &Path| {
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut characters: Vec<Character> = saved_variables::account_dirs(&wtf)
+        .into_iter()
+        .flat_map(|account| {
+            saved_variables::character_dirs(&account)
+                .into_iter()
+                .map(|dir| Character {
+                    account: name(&account),
+                    realm: dir.parent().map(name).unwrap_or_default(),
+                    name: name(&dir),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    characters
+        .sort_by(|a, b| (&a.account, &a.realm, &a.name).cmp(&(&b.account, &b.realm, &b.name)));
+    characters
+}
+
+/// `AddOns.txt` of a known character; names are checked against the
+/// directories on disk so they cannot point outside of `WTF`
+fn addons_file(addons_dir: &Path, character: &Character) -> Result<PathBuf, AddonError> {
+    if !list_characters(addons_dir).contains(character) {
+        return Err(AddonError::new(
+            AddonErrorKind::NotFound,
+            format!(
+                "Character {} on {} ({}) not found",
+                character.name, character.realm, character.account
+            ),
+        )
+        .with_path(addons_dir));
+    }
+    let wtf = saved_variables::wtf_dir(addons_dir)
+        .ok_or_else(|| AddonError::new(AddonErrorKind::NotFound, "WTF directory not found"))?;
+    Ok(wtf
+        .join("Account")
+        .join(&character.account)
+        .join(&character.realm)
+        .join(&character.name)
+        .join(ADDONS_FILE))
+}
+
+fn read_addons_file(path: &Path) -> Result<AddOnsTxt, AddonError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(AddOnsTxt::parse(&content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(AddOnsTxt::default()),
+        Err(e) => Err(AddonError::io("Failed to read AddOns.txt", &e).with_path(path)),
+    }
+}
+
+/// Sub-addons of every managed repository that are linked into the AddOns folder,
+/// as (repo name, addon name)
+fn managed_addons(addons_dir: &Path) -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir(addons_dir.join(".addonmanager")) else {
+        return Vec::new();
+    };
+    let mut addons: Vec<(String, String)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_dir()
+                && !path
+                    .file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        })
+        .flat_map(|repo_dir| {
+            let repo_name = repo_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            addon_disk::find_all_sub_addons(&repo_dir)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|addon| deploy::is_linked(addons_dir, &addon.name))
+                .map(move |addon| (repo_name.clone(), addon.name))
+        })
+        .collect();
+    addons.sort();
+    addons
+}
+
+/// Managed sub-addons with their enabled state for a character
+pub fn list_character_addons(
+    addons_dir: &Path,
+    character: &Character,
+) -> Result<Vec<CharacterAddon>, AddonError> {
+    let file = read_addons_file(&addons_file(addons_dir, character)?)?;
+    Ok(managed_addons(addons_dir)
+        .into_iter()
+        .map(|(repo_name, name)| CharacterAddon {
+            enabled: file.is_enabled(&name).unwrap_or(true),
+            name,
+            repo_name,
+        })
+        .collect())
+}
+
+/// Enable or disable a managed sub-addon for a single character.
+/// The symlink in the AddOns folder is left untouched.
+pub fn set_character_addon_enabled(
+    addons_dir: &Path,
+    character: &Character,
+    addon_name: &str,
+    enabled: bool,
+) -> Result<(), AddonError> {
+    if !managed_addons(addons_dir)
+        .iter()
+        .any(|(_, name)| name == addon_name)
+    {
+        return Err(AddonError::new(
+            AddonErrorKind::NotFound,
+            format!("{addon_name} is not a linked managed addon"),
+        )
+        .with_path(addons_dir));
+    }
+    let path = addons_file(addons_dir, character)?;
+    let mut file = read_addons_file(&path)?;
+    file.set_enabled(addon_name, enabled);
+    fs::write(&path, file.content())
+        .map_err(|e| AddonError::io("Failed to write AddOns.txt", &e).with_path(&path))
+}
+
+#[tauri::command]
+pub async fn list_characters_cmd(path: String) -> Result<Vec<Character>, String> {
+    Ok(list_characters(Path::new(&path)))
+}
+
+#[tauri::command]
+pub async fn list_character_addons_cmd(
+    path: String,
+    character: Character,
+) -> Result<Vec<CharacterAddon>, String> {
+    list_character_addons(Path::new(&path), &character).map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn set_character_addon_enabled_cmd(
+    app_handle: AppHandle,
+    path: String,
+    character: Character,
+    addon_name: String,
+    enabled: bool,
+) -> Result<(), String> {
+    set_character_addon_enabled(Path::new(&path), &character, &addon_name, enabled)?;
+    app_handle
+        .emit("addon-data-updated", ())
+        .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symlink;
+    use crate::test_utils::{print_dir_tree, setup_addons_dir};
+    use crate::validate;
+
+    #[test]
+    fn test_toggle_preserves_format() {
+        let content = "Blizzard_AuctionUI: enabled\r\nFakeAddon: enabled\r\nOther:disabled";
+        let mut file = AddOnsTxt::parse(content);
+        assert_eq!(file.is_enabled("fakeaddon"), Some(true));
+        assert_eq!(file.is_enabled("Missing"), None);
+
+        file.set_enabled("FakeAddon", false);
+        file.set_enabled("Other", true);
+        file.set_enabled("NewAddon", false);
+        assert_eq!(
+            file.content(),
+            "Blizzard_AuctionUI: enabled\r\nFakeAddon: disabled\r\nOther:enabled\r\nNewAddon: disabled\r\n"
+        );
+
+        let mut file = AddOnsTxt::parse(content);
+        file.set_enabled("FakeAddon", true);
+        assert_eq!(file.content(), content, "Unchanged values must round trip");
+    }
+
+    #[test]
+    fn test_disable_addon_for_one_character() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let repo_dir = validate::ensure_manager_dir(&addons_dir)
+            .unwrap()
+            .join("FakeRepo");
+        fs::create_dir_all(repo_dir.join("FakeAddon")).unwrap();
+        fs::write(repo_dir.join("FakeAddon").join("FakeAddon.toc"), "").unwrap();
+        let link = addons_dir.join("FakeAddon");
+        symlink::create_symlink(repo_dir.join("FakeAddon"), &link).unwrap();
+
+        let account = addons_dir
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join("WTF/Account/ACCOUNT");
+        fs::create_dir_all(account.join("SavedVariables")).unwrap();
+        fs::create_dir_all(account.join("Realm/Alt")).unwrap();
+        fs::create_dir_all(account.join("Realm/Main")).unwrap();
+        fs::write(
+            account.join("Realm/Main/AddOns.txt"),
+            "FakeAddon: enabled\n",
+        )
+        .unwrap();
+
+        let characters = list_characters(&addons_dir);
+        assert_eq!(
+            characters
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Alt", "Main"]
+        );
+        let main = &characters[1];
+
+        set_character_addon_enabled(&addons_dir, main, "FakeAddon", false).unwrap();
+        print_dir_tree(account.to_str().unwrap());
+
+        assert_eq!(
+            fs::read_to_string(account.join("Realm/Main/AddOns.txt")).unwrap(),
+            "FakeAddon: disabled\n"
+        );
+        assert!(!list_character_addons(&addons_dir, main).unwrap()[0].enabled);
+        assert!(list_character_addons(&addons_dir, &characters[0]).unwrap()[0].enabled);
+        assert!(link.is_symlink(), "Symlink must stay in place");
+    }
+}
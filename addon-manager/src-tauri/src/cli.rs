@@ -0,0 +1,1206 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use clap::{Subcommand, ValueEnum};
+use serde::Serialize;
+
+use crate::{
+    addon_discovery,
+    addon_disk::{DiskAddOnsFolder, DiskAddonRepository, DiskAddonSource},
+    addon_store::{self, AddOnsUserConfig, ConfigStore},
+    backup, deploy,
+    doctor::{self, DoctorReport},
+    error::{AddonError, AddonErrorKind},
+    install,
+    operation_history::OperationHistory,
+    operation_registry::CancellationToken,
+    operation_reporter::{
+        EventSink, OperationEvent, OperationEventPayload, OperationKey, OperationType,
+        emit_operation_event,
+    },
+    plan::{self, OperationPlan},
+    remove,
+    saved_variables::SavedVariablesAction,
+    trash,
+    update::{self, OutdatedRepo, UpdateFilter},
+    view_models::{self, AddonSource},
+};
+
+/// The command succeeded
+pub const EXIT_SUCCESS: i32 = 0;
+/// The command, or one of the operations it ran, failed
+pub const EXIT_FAILURE: i32 = 1;
+/// Invalid or ambiguous arguments, the same code clap uses for parse errors
+pub const EXIT_USAGE: i32 = 2;
+/// The folder, repository or addon does not exist
+pub const EXIT_NOT_FOUND: i32 = 3;
+/// `check` found repositories that are behind their remote branch
+pub const EXIT_UPDATES_AVAILABLE: i32 = 4;
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List configured AddOns folders with their repositories and addons
+    List,
+    /// Fetch every repository and show which ones are outdated
+    Status,
+    /// Fetch every repository and list the outdated ones without changing anything.
+    /// Exits with 4 when updates are available.
+    Check {
+        #[command(flatten)]
+        filter: UpdateFilter,
+    },
+    /// Clone a repository and link its sub-addons
+    Install {
+        url: String,
+        /// AddOns folder, may be omitted when only one is configured
+        #[arg(long)]
+        folder: Option<String>,
+    },
+    /// Update a repository by name or URL, or every outdated repository
+    /// that matches the filters and is not excluded from bulk updates
+    Update {
+        repo: Option<String>,
+        #[command(flatten)]
+        filter: UpdateFilter,
+        /// Print the planned changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Move a repository and its links into the trash
+    Remove {
+        repo: String,
+        /// AddOns folder, may be omitted when only one is configured
+        #[arg(long)]
+        folder: Option<String>,
+        /// What to do with the saved variables of the repository's addons
+        #[arg(long, value_enum, default_value = "keep")]
+        saved_variables: SavedVariablesAction,
+    },
+    /// Link a sub-addon of an installed repository into the AddOns folder
+    Link {
+        addon: String,
+        /// AddOns folder, may be omitted when only one is configured
+        #[arg(long)]
+        folder: Option<String>,
+    },
+    /// Remove the link of a sub-addon from the AddOns folder
+    Unlink {
+        addon: String,
+        /// AddOns folder, may be omitted when only one is configured
+        #[arg(long)]
+        folder: Option<String>,
+    },
+    /// Add an AddOns folder to the configuration. A game directory is accepted
+    /// too, its `Interface/AddOns` is created when missing.
+    AddFolder { path: String },
+    /// Remove an AddOns folder from the configuration, its files are left alone
+    RemoveFolder { path: String },
+    /// Print the repositories of every folder in the import format
+    Export {
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Install the repositories listed in an export that are not installed yet
+    Import {
+        file: PathBuf,
+        /// Install into this folder instead of the `# Directory:` of each section
+        #[arg(long)]
+        folder: Option<String>,
+    },
+    /// Check configured AddOns folders for problems and suggest fixes.
+    /// Exits with 1 when any error was found.
+    Doctor,
+}
+
+/// Failure of a CLI command with the exit code the process should end with
+#[derive(Debug, Serialize)]
+pub struct CliError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl CliError {
+    fn new(code: i32, message: impl Into<String>) -> Self {
+        CliError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<AddonError> for CliError {
+    fn from(error: AddonError) -> Self {
+        let code = match error.kind {
+            AddonErrorKind::NotFound => EXIT_NOT_FOUND,
+            AddonErrorKind::InvalidInput => EXIT_USAGE,
+            _ => EXIT_FAILURE,
+        };
+        CliError::new(code, error.to_string())
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::new(EXIT_FAILURE, message)
+    }
+}
+
+/// Run a single subcommand against the configured folders
+pub fn run<S, E>(store: &S, events: &E, command: Command) -> Result<Output, CliError>
+where
+    S: ConfigStore,
+    E: EventSink + Sync,
+{
+    let config = addon_store::load_user_config(store)?;
+    match command {
+        Command::List => list(&config),
+        Command::Status => status(&config, events),
+        Command::Check { filter } => check(&config, &filter, events),
+        Command::Install { url, folder } => {
+            let path = resolve_folder(&config, folder.as_deref())?;
+            install(store, events, &url, &path).map(|()| Output::None)
+        }
+        Command::Update {
+            repo,
+            filter,
+            dry_run,
+        } => update(store, events, &config, repo.as_deref(), &filter, dry_run),
+        Command::Remove {
+            repo,
+            folder,
+            saved_variables,
+        } => {
+            let path = resolve_folder(&config, folder.as_deref())?;
+            remove(store, events, &repo, &path, saved_variables).map(|()| Output::None)
+        }
+        Command::Link { addon, folder } => {
+            let path = resolve_folder(&config, folder.as_deref())?;
+            set_linked(store, events, &addon, &path, true).map(|()| Output::None)
+        }
+        Command::Unlink { addon, folder } => {
+            let path = resolve_folder(&config, folder.as_deref())?;
+            set_linked(store, events, &addon, &path, false).map(|()| Output::None)
+        }
+        Command::AddFolder { path } => {
+            let key = OperationKey {
+                repo_url: String::new(),
+                folder_path: path.clone(),
+            };
+            addon_store::add_game_folder(store, &path, |event| {
+                if let Err(e) = emit_operation_event(events, &key, event) {
+                    eprintln!("Failed to emit operation-event: {e}");
+                }
+            })?;
+            Ok(Output::None)
+        }
+        Command::RemoveFolder { path } => {
+            resolve_folder(&config, Some(&path))?;
+            addon_store::remove_folder(store, &path)?;
+            Ok(Output::None)
+        }
+        Command::Export { output } => {
+            let folders = scan_folders(&config)?;
+            let text = export_text(&folders);
+            match output {
+                Some(file) => fs::write(&file, text).map(|()| Output::None).map_err(|e| {
+                    CliError::new(
+                        EXIT_FAILURE,
+                        format!("Failed to write {}: {e}", file.display()),
+                    )
+                }),
+                None => Ok(Output::Export(text)),
+            }
+        }
+        Command::Import { file, folder } => {
+            let text = fs::read_to_string(&file).map_err(|e| {
+                CliError::new(
+                    EXIT_NOT_FOUND,
+                    format!("Failed to read {}: {e}", file.display()),
+                )
+            })?;
+            import(store, events, &config, &text, folder.as_deref()).map(|()| Output::None)
+        }
+        Command::Doctor => Ok(Output::Doctor(doctor::diagnose(store)?)),
+    }
+}
+
+/// How results and events are written to stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable lines
+    #[default]
+    Text,
+    /// The result as a single JSON document, events are only printed as text warnings and errors
+    Json,
+    /// Every operation event as a JSON line, followed by a line with the result
+    Ndjson,
+}
+
+/// Result of a subcommand
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Output {
+    None,
+    Folders(Vec<view_models::AddOnsFolder>),
+    Plans(Vec<OperationPlan>),
+    Outdated(Vec<OutdatedRepo>),
+    /// All outdated repositories were updated
+    UpToDate(UpdateSummary),
+    /// Export in the import format
+    Export(String),
+    Doctor(DoctorReport),
+}
+
+/// Result of an `update` that left no outdated repositories
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSummary {
+    pub scanned_folders: usize,
+    pub updated: usize,
+}
+
+/// A line of the `--format ndjson` stream
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum StreamLine<'a> {
+    Event(&'a OperationEventPayload),
+    Result { data: &'a Output },
+    Error(&'a CliError),
+}
+
+fn print_json(value: &impl Serialize) {
+    match serde_json::to_string(value) {
+        Ok(json) => println!("{json}"),
+        Err(e) => eprintln!("Failed to serialize output: {e}"),
+    }
+}
+
+/// Print the result for a terminal. `quiet` only hides the update summary.
+fn print_text(output: &Output, quiet: bool) {
+    match output {
+        Output::None => {}
+        Output::UpToDate(_) if quiet => {}
+        Output::UpToDate(summary) => {
+            println!("Scanned {} AddOns folders", summary.scanned_folders);
+            if summary.updated > 0 {
+                println!("Updated {} repositories", summary.updated);
+            }
+            println!("All addons are up-to-date");
+        }
+        Output::Folders(folders) => {
+            for folder in folders {
+                println!("{}", folder.path);
+                for repo in &folder.repositories {
+                    match &repo.source {
+                        AddonSource::Git {
+                            repo_name,
+                            current_branch,
+                            repo_ref,
+                            latest_ref,
+                            ..
+                        } => {
+                            let state = match latest_ref {
+                                None => String::new(),
+                                Some(_) if latest_ref == repo_ref => " up-to-date".to_string(),
+                                Some(_) => {
+                                    format!(" outdated -> {}", short_ref(latest_ref.as_deref()))
+                                }
+                            };
+                            println!(
+                                "  {repo_name} [{} {}]{state}",
+                                current_branch.as_deref().unwrap_or("detached"),
+                                short_ref(repo_ref.as_deref())
+                            );
+                        }
+                        AddonSource::Local { folder_name, .. } => {
+                            println!("  {folder_name} [local]")
+                        }
+                    }
+                    for addon in &repo.addons {
+                        let mark = if addon.is_symlinked { "x" } else { " " };
+                        println!("    [{mark}] {}", addon.name);
+                    }
+                }
+            }
+        }
+        Output::Plans(plans) if plans.is_empty() => println!("All addons are up-to-date"),
+        Output::Plans(plans) => {
+            for plan in plans {
+                println!("{plan}");
+            }
+        }
+        Output::Outdated(repos) if repos.is_empty() => println!("All addons are up-to-date"),
+        Output::Outdated(repos) => {
+            for repo in repos {
+                let behind = match repo.behind {
+                    Some(n) => format!(", {n} commits behind"),
+                    None => String::new(),
+                };
+                println!(
+                    "{}: {} ({}) {} -> {}{behind}",
+                    repo.folder_path,
+                    repo.repo_name,
+                    repo.branch,
+                    short_ref(Some(&repo.local_ref)),
+                    short_ref(Some(&repo.remote_ref)),
+                );
+            }
+        }
+        Output::Export(text) => print!("{text}"),
+        Output::Doctor(report) => println!("{report}"),
+    }
+}
+
+/// Run a subcommand, print its result in the given format and return the exit code.
+/// Finished operations are appended to `history` when given.
+pub fn execute(
+    store: &impl ConfigStore,
+    command: Command,
+    quiet: bool,
+    format: OutputFormat,
+    history: Option<OperationHistory>,
+) -> i32 {
+    let result = match format {
+        OutputFormat::Ndjson => run(store, &NdjsonEvents { history }, command),
+        OutputFormat::Json => run(
+            store,
+            &ConsoleEvents {
+                quiet: true,
+                history,
+            },
+            command,
+        ),
+        OutputFormat::Text => run(store, &ConsoleEvents { quiet, history }, command),
+    };
+    match (&result, format) {
+        (Ok(output), OutputFormat::Text) => print_text(output, quiet),
+        (Ok(output), OutputFormat::Json) => print_json(output),
+        (Ok(output), OutputFormat::Ndjson) => print_json(&StreamLine::Result { data: output }),
+        (Err(e), OutputFormat::Text) => eprintln!("Error: {e}"),
+        (Err(e), OutputFormat::Json) => print_json(&serde_json::json!({ "error": e })),
+        (Err(e), OutputFormat::Ndjson) => print_json(&StreamLine::Error(e)),
+    }
+    match result {
+        Ok(Output::Outdated(repos)) if !repos.is_empty() => EXIT_UPDATES_AVAILABLE,
+        Ok(Output::Doctor(report)) if report.has_errors() => EXIT_FAILURE,
+        Ok(_) => EXIT_SUCCESS,
+        Err(e) => e.code,
+    }
+}
+
+/// Writes every operation event to stdout as a JSON line
+pub struct NdjsonEvents {
+    pub history: Option<OperationHistory>,
+}
+
+impl EventSink for NdjsonEvents {
+    fn operation_event(&self, payload: OperationEventPayload) -> Result<(), String> {
+        if let Some(history) = &self.history {
+            history.record(&payload.key, &payload.event);
+        }
+        let line =
+            serde_json::to_string(&StreamLine::Event(&payload)).map_err(|e| e.to_string())?;
+        println!("{line}");
+        Ok(())
+    }
+
+    fn finish_operation(&self, key: &OperationKey) {
+        if let Some(history) = &self.history {
+            history.finish(key);
+        }
+    }
+}
+
+/// Prints operation events to the terminal
+pub struct ConsoleEvents {
+    /// Only print warnings and errors
+    pub quiet: bool,
+    pub history: Option<OperationHistory>,
+}
+
+impl EventSink for ConsoleEvents {
+    fn operation_event(&self, payload: OperationEventPayload) -> Result<(), String> {
+        if let Some(history) = &self.history {
+            history.record(&payload.key, &payload.event);
+        }
+        match payload.event {
+            OperationEvent::Status(message) if !self.quiet => println!("{message}"),
+            OperationEvent::Warning(message) => eprintln!("Warning: {message}"),
+            OperationEvent::Error(error) => eprintln!("Error: {error}"),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn finish_operation(&self, key: &OperationKey) {
+        if let Some(history) = &self.history {
+            history.finish(key);
+        }
+    }
+}
+
+/// Forwards the events of a single operation to the sink, wrapped in
+/// `Started` and `Completed` or `Error` like the app's commands do,
+/// and finishes it in the sink's history
+struct Operation<'a, E> {
+    events: &'a E,
+    key: OperationKey,
+    operation: OperationType,
+    /// Errors reported while the operation kept going, e.g. a sub-addon that failed to link
+    errors: usize,
+}
+
+impl<'a, E: EventSink + Sync> Operation<'a, E> {
+    fn start(events: &'a E, key: OperationKey, operation: OperationType) -> Self {
+        let op = Operation {
+            events,
+            key,
+            operation,
+            errors: 0,
+        };
+        op.emit(OperationEvent::Started {
+            operation: op.operation.clone(),
+        });
+        op
+    }
+
+    fn emit(&self, event: OperationEvent) {
+        if let Err(e) = emit_operation_event(self.events, &self.key, event) {
+            eprintln!("Failed to emit operation-event: {e}");
+        }
+    }
+
+    fn status(&self, message: impl Into<String>) {
+        self.emit(OperationEvent::Status(message.into()));
+    }
+
+    fn reporter(&mut self) -> impl FnMut(OperationEvent) + Send + '_ {
+        move |event| {
+            if matches!(event, OperationEvent::Error(_)) {
+                self.errors += 1;
+            }
+            self.emit(event);
+        }
+    }
+
+    fn finish<T>(self, result: Result<T, AddonError>) -> Result<T, CliError> {
+        let subject = match self.key.repo_url.as_str() {
+            "" => self.key.folder_path.clone(),
+            url => url.to_string(),
+        };
+        let outcome = match result {
+            Ok(value) if self.errors == 0 => {
+                self.emit(OperationEvent::Completed);
+                Ok(value)
+            }
+            Ok(_) => {
+                self.emit(OperationEvent::Completed);
+                Err(CliError::new(
+                    EXIT_FAILURE,
+                    format!(
+                        "{:?} of {subject} finished with {} errors",
+                        self.operation, self.errors
+                    ),
+                ))
+            }
+            Err(error) => {
+                let code = CliError::from(error.clone()).code;
+                self.emit(OperationEvent::Error(error));
+                Err(CliError::new(
+                    code,
+                    format!("{:?} of {subject} failed", self.operation),
+                ))
+            }
+        };
+        self.events.finish_operation(&self.key);
+        outcome
+    }
+}
+
+/// The configured folder matching `folder`, or the only configured folder
+fn resolve_folder(config: &AddOnsUserConfig, folder: Option<&str>) -> Result<String, CliError> {
+    match folder {
+        Some(path) => config
+            .folders
+            .iter()
+            .find(|f| f.path == path)
+            .map(|f| f.path.clone())
+            .ok_or_else(|| {
+                CliError::new(
+                    EXIT_NOT_FOUND,
+                    format!("AddOns folder is not configured: {path}"),
+                )
+            }),
+        None => match config.folders.as_slice() {
+            [folder] => Ok(folder.path.clone()),
+            [] => Err(CliError::new(
+                EXIT_NOT_FOUND,
+                "No AddOns folders configured, add one with add-folder",
+            )),
+            _ => Err(CliError::new(
+                EXIT_USAGE,
+                "Multiple AddOns folders configured, select one with --folder",
+            )),
+        },
+    }
+}
+
+fn scan_folders(config: &AddOnsUserConfig) -> Result<Vec<DiskAddOnsFolder>, CliError> {
+    config
+        .folders
+        .iter()
+        .map(|folder| DiskAddOnsFolder::scan_disk_only(&folder.path).map_err(Into::into))
+        .collect()
+}
+
+/// A repository matches by URL or, ignoring case, by name
+fn matches_repo(repo: &DiskAddonRepository, query: &str) -> bool {
+    repo.get_key() == query || repo.get_name().eq_ignore_ascii_case(query)
+}
+
+fn short_ref(git_ref: Option<&str>) -> &str {
+    git_ref.map(|r| &r[..r.len().min(7)]).unwrap_or("-")
+}
+
+fn list(config: &AddOnsUserConfig) -> Result<Output, CliError> {
+    let folders = scan_folders(config)?;
+    Ok(Output::Folders(addon_discovery::merge_user_meta(
+        folders, config,
+    )))
+}
+
+/// Scan a folder fetching every repository, reporting fetch problems as warnings
+fn scan_remote(path: &str, events: &impl EventSink) -> Result<DiskAddOnsFolder, AddonError> {
+    DiskAddOnsFolder::scan_with_cancel(path, &CancellationToken::default(), events)
+}
+
+fn status(config: &AddOnsUserConfig, events: &impl EventSink) -> Result<Output, CliError> {
+    let folders = config
+        .folders
+        .iter()
+        .map(|folder| scan_remote(&folder.path, events))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Output::Folders(addon_discovery::merge_user_meta(
+        folders, config,
+    )))
+}
+
+/// Configured folders selected by the filter; every folder it names must be configured
+fn filtered_folders(
+    config: &AddOnsUserConfig,
+    filter: &UpdateFilter,
+) -> Result<Vec<String>, CliError> {
+    for path in &filter.folders {
+        resolve_folder(config, Some(path))?;
+    }
+    Ok(config
+        .folders
+        .iter()
+        .filter(|f| filter.includes_folder(&f.path))
+        .map(|f| f.path.clone())
+        .collect())
+}
+
+fn check(
+    config: &AddOnsUserConfig,
+    filter: &UpdateFilter,
+    events: &impl EventSink,
+) -> Result<Output, CliError> {
+    let folders = filtered_folders(config, filter)?
+        .iter()
+        .map(|path| scan_remote(path, events))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Output::Outdated(update::outdated_repos(
+        &folders, config, filter,
+    )))
+}
+
+fn install(
+    store: &impl ConfigStore,
+    events: &(impl EventSink + Sync),
+    url: &str,
+    path: &str,
+) -> Result<(), CliError> {
+    let key = OperationKey {
+        repo_url: url.to_string(),
+        folder_path: path.to_string(),
+    };
+    let mut op = Operation::start(events, key, OperationType::Install);
+    let link = deploy::configured_link_options(store, path);
+    let preferred_branch = addon_store::load_user_config(store)?
+        .folder_settings(path)
+        .preferred_branch;
+    let result = install::install_addon(
+        url.to_string(),
+        path.to_string(),
+        link,
+        preferred_branch.as_deref(),
+        &CancellationToken::default(),
+        op.reporter(),
+    );
+    if let Ok(linked) = &result {
+        install::save_linked(store, &op.key, linked);
+    }
+    op.finish(result).map(|_| ())
+}
+
+fn update(
+    store: &impl ConfigStore,
+    events: &(impl EventSink + Sync),
+    config: &AddOnsUserConfig,
+    repo: Option<&str>,
+    filter: &UpdateFilter,
+    dry_run: bool,
+) -> Result<Output, CliError> {
+    let paths = filtered_folders(config, filter)?;
+    let scanned_folders = paths.len();
+
+    let mut tasks = Vec::new();
+    for path in paths {
+        match repo {
+            Some(query) => {
+                let disk_folder = DiskAddOnsFolder::scan_disk_only(&path)?;
+                for found in disk_folder
+                    .repositories
+                    .iter()
+                    .filter(|r| matches_repo(r, query))
+                {
+                    match &found.source {
+                        DiskAddonSource::Git {
+                            repo_url,
+                            current_branch: Some(branch),
+                            ..
+                        } => tasks.push((path.clone(), repo_url.clone(), branch.clone())),
+                        _ => {
+                            let key = OperationKey {
+                                repo_url: found.get_key(),
+                                folder_path: path.clone(),
+                            };
+                            let message = format!(
+                                "{} in {path} is not on a git branch, skipping",
+                                found.get_name()
+                            );
+                            emit_operation_event(events, &key, OperationEvent::Warning(message))?;
+                        }
+                    }
+                }
+            }
+            None => {
+                let disk_folder = scan_remote(&path, events)?;
+                tasks.extend(update::pending_updates(
+                    &HashMap::from([(path, disk_folder)]),
+                    config,
+                    filter,
+                ));
+            }
+        }
+    }
+
+    if tasks.is_empty() {
+        return match repo {
+            Some(query) => Err(CliError::new(
+                EXIT_NOT_FOUND,
+                format!("Repository not found: {query}"),
+            )),
+            None => Ok(Output::UpToDate(UpdateSummary {
+                scanned_folders,
+                updated: 0,
+            })),
+        };
+    }
+
+    if dry_run {
+        let plans = tasks
+            .iter()
+            .map(|(path, url, branch)| plan::plan_update(path, url, branch))
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(Output::Plans(plans));
+    }
+
+    let backup_count = backup::configured_backup_count(store);
+    let mut failed = 0;
+    for (path, url, branch) in &tasks {
+        let key = OperationKey {
+            repo_url: url.clone(),
+            folder_path: path.clone(),
+        };
+        let mut op = Operation::start(events, key, OperationType::Update);
+        op.status(format!("Updating {url} ({branch}) in {path}"));
+        let result = update::update_addon_repo(
+            path,
+            url,
+            branch,
+            &CancellationToken::default(),
+            backup_count,
+            op.reporter(),
+        );
+        if op.finish(result).is_err() {
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(CliError::new(
+            EXIT_FAILURE,
+            format!("{failed} of {} updates failed", tasks.len()),
+        ));
+    }
+    Ok(Output::UpToDate(UpdateSummary {
+        scanned_folders,
+        updated: tasks.len(),
+    }))
+}
+
+fn remove(
+    store: &impl ConfigStore,
+    events: &(impl EventSink + Sync),
+    query: &str,
+    path: &str,
+    saved_variables: SavedVariablesAction,
+) -> Result<(), CliError> {
+    let disk_folder = DiskAddOnsFolder::scan_disk_only(path)?;
+    let repo = disk_folder
+        .repositories
+        .iter()
+        .find(|r| matches_repo(r, query))
+        .ok_or_else(|| CliError::new(EXIT_NOT_FOUND, format!("Repository not found: {query}")))?;
+
+    let key = OperationKey {
+        repo_url: repo.get_key(),
+        folder_path: path.to_string(),
+    };
+    let mut op = Operation::start(events, key, OperationType::Delete);
+    let result = remove::delete_addon_files(&repo.get_key(), path, saved_variables, op.reporter());
+    trash::purge_expired_with_config(store, Path::new(path));
+    if let Ok(Some(entry)) = &result {
+        op.status(format!(
+            "Moved {} to the trash as {}",
+            entry.repo_name, entry.id
+        ));
+    }
+    op.finish(result).map(|_| ())
+}
+
+fn set_linked(
+    store: &impl ConfigStore,
+    events: &(impl EventSink + Sync),
+    addon_name: &str,
+    path: &str,
+    enabled: bool,
+) -> Result<(), CliError> {
+    let disk_folder = DiskAddOnsFolder::scan_disk_only(path)?;
+    let (repo, addon) = disk_folder
+        .repositories
+        .iter()
+        .find_map(|repo| {
+            repo.addons
+                .iter()
+                .find(|a| a.name.eq_ignore_ascii_case(addon_name))
+                .map(|addon| (repo, addon))
+        })
+        .ok_or_else(|| CliError::new(EXIT_NOT_FOUND, format!("Addon not found: {addon_name}")))?;
+
+    let key = OperationKey {
+        repo_url: repo.get_key(),
+        folder_path: path.to_string(),
+    };
+    let (operation, action) = if enabled {
+        (OperationType::Install, "Linked")
+    } else {
+        (OperationType::Delete, "Unlinked")
+    };
+    let op = Operation::start(events, key, operation);
+    let addons_dir = Path::new(path);
+    let result = if enabled {
+        let repo_root = addons_dir.join(".addonmanager").join(repo.get_name());
+        let link = deploy::configured_link_options(store, path);
+        install::link_sub_addon(addon, &repo_root, addons_dir, link)
+    } else {
+        deploy::unlink_addon(addons_dir, &addon.name)
+    }
+    .and_then(|()| {
+        addon_store::set_addon_enabled(store, path, &repo.get_key(), &addon.name, enabled)
+    });
+    if result.is_ok() {
+        op.status(format!("{action} {} from {}", addon.name, repo.get_name()));
+    }
+    op.finish(result)
+}
+
+/// Export in the format of the import dialog:
+/// a `# Directory:` header per folder followed by `<addonName> *<gitUrl> <branch>` lines
+pub fn export_text(folders: &[DiskAddOnsFolder]) -> String {
+    let mut text = String::new();
+    for folder in folders {
+        text.push_str(&format!("# Directory: {}\n", folder.path));
+        text.push_str("# <addonName> *<gitUrl> <branch>\n");
+        let mut seen = Vec::new();
+        for repo in &folder.repositories {
+            let DiskAddonSource::Git {
+                repo_url,
+                repo_name,
+                current_branch,
+                ..
+            } = &repo.source
+            else {
+                continue;
+            };
+            if seen.contains(&repo_url) {
+                continue;
+            }
+            seen.push(repo_url);
+            let name = repo.addons.first().map_or(repo_name, |addon| &addon.name);
+            let branch = current_branch.as_deref().unwrap_or("main");
+            text.push_str(&format!("{name} *{repo_url} {branch}\n"));
+        }
+    }
+    text
+}
+
+/// A repository to install, parsed from a line of an export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportEntry {
+    /// From the legacy `<path> <addonName>` prefix or the preceding `# Directory:` header
+    pub folder: Option<String>,
+    pub url: String,
+    pub branch: String,
+}
+
+/// Whether a legacy folder prefix is an absolute path, `/...` or `C:\...`
+fn is_absolute_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with('/')
+        || (bytes.len() > 2
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'\\' | b'/'))
+}
+
+/// Split the legacy `<path> <addonName>` prefix. Addon names may contain spaces,
+/// so only a path ending in `Interface/AddOns` or an absolute path is a folder.
+fn split_legacy_folder(before: &str) -> Option<&str> {
+    const ADDONS_DIR: &str = "interface/addons ";
+    let normalized = before.to_ascii_lowercase().replace('\\', "/");
+    if let Some(start) = normalized.find(ADDONS_DIR) {
+        return Some(&before[..start + ADDONS_DIR.len() - 1]);
+    }
+    before
+        .rsplit_once(' ')
+        .map(|(path, _name)| path.trim())
+        .filter(|path| is_absolute_path(path))
+}
+
+/// Parse `[<path> ]<addonName> *<gitUrl> <branch>`; the addon name is not needed to install
+fn parse_import_line(line: &str) -> Result<ImportEntry, String> {
+    let (before, rest) = line.split_once('*').ok_or("No git URL found (missing *)")?;
+    let mut parts = rest.split_whitespace();
+    let url = parts.next().ok_or("Git URL is empty")?;
+    let branch = parts.next().ok_or("Branch is empty")?;
+    let before = before.trim();
+    if before.is_empty() {
+        return Err("Addon name is empty".to_string());
+    }
+    let folder = split_legacy_folder(before).map(str::to_string);
+    Ok(ImportEntry {
+        folder,
+        url: url.to_string(),
+        branch: branch.to_string(),
+    })
+}
+
+/// Parse an export, numbering entries by line. `#` and `//` lines are comments,
+/// except for `# Directory:` which selects the folder of the following lines.
+pub fn parse_import(text: &str) -> Vec<(usize, Result<ImportEntry, String>)> {
+    let mut directory: Option<String> = None;
+    let mut entries = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(path) = line.strip_prefix("# Directory:") {
+            directory = Some(path.trim().to_string());
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+        let entry = parse_import_line(line).map(|mut entry| {
+            entry.folder = entry.folder.or_else(|| directory.clone());
+            entry
+        });
+        entries.push((index + 1, entry));
+    }
+    entries
+}
+
+fn import(
+    store: &impl ConfigStore,
+    events: &(impl EventSink + Sync),
+    config: &AddOnsUserConfig,
+    text: &str,
+    folder: Option<&str>,
+) -> Result<(), CliError> {
+    let override_folder = folder
+        .map(|f| resolve_folder(config, Some(f)))
+        .transpose()?;
+    let entries = parse_import(text);
+    let mut failed = 0;
+
+    for (line, entry) in &entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                eprintln!("Error: line {line}: {e}");
+                failed += 1;
+                continue;
+            }
+        };
+        let path = match override_folder.clone().or_else(|| entry.folder.clone()) {
+            Some(path) => path,
+            None => match resolve_folder(config, None) {
+                Ok(path) => path,
+                Err(e) => {
+                    eprintln!("Error: line {line}: {e}");
+                    failed += 1;
+                    continue;
+                }
+            },
+        };
+        if let Err(e) = addon_store::add_folder(store, &path) {
+            eprintln!("Error: line {line}: {e}");
+            failed += 1;
+            continue;
+        }
+
+        let key = OperationKey {
+            repo_url: entry.url.clone(),
+            folder_path: path.clone(),
+        };
+        let installed = remove::repo_name_from_key(&entry.url)
+            .map(|name| Path::new(&path).join(".addonmanager").join(name).is_dir())
+            .unwrap_or(false);
+        if installed {
+            emit_operation_event(
+                events,
+                &key,
+                OperationEvent::Status(format!("{} is already installed in {path}", entry.url)),
+            )?;
+            continue;
+        }
+
+        let mut op = Operation::start(events, key, OperationType::Install);
+        op.status(format!(
+            "Installing {} ({}) into {path}",
+            entry.url, entry.branch
+        ));
+        let link = deploy::configured_link_options(store, &path);
+        let result = install::install_addon(
+            entry.url.clone(),
+            path.clone(),
+            link,
+            Some(&entry.branch),
+            &CancellationToken::default(),
+            op.reporter(),
+        );
+        if let Ok(linked) = &result {
+            install::save_linked(store, &op.key, linked);
+        }
+        if op.finish(result).is_err() {
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        return Err(CliError::new(
+            EXIT_FAILURE,
+            format!("{failed} of {} imports failed", entries.len()),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addon_store::FileConfigStore;
+    use crate::test_utils::{print_dir_tree, setup_addons_dir};
+    use crate::{symlink, validate};
+
+    #[test]
+    fn test_cli_links_with_file_config_store() {
+        let (temp, addons_dir) = setup_addons_dir();
+        let store = FileConfigStore::new(temp.path().join("config/addon-manager.json"));
+        let events = ConsoleEvents {
+            quiet: false,
+            history: Some(OperationHistory::new(temp.path().join("data"))),
+        };
+        let path = addons_dir.to_string_lossy().to_string();
+        run(&store, &events, Command::AddFolder { path: path.clone() }).unwrap();
+
+        let addon_dir = validate::ensure_manager_dir(&addons_dir)
+            .unwrap()
+            .join("LocalRepo/SubAddon");
+        fs::create_dir_all(&addon_dir).unwrap();
+        fs::write(addon_dir.join("SubAddon.toc"), "## Title: Sub").unwrap();
+
+        let link = |addon: &str| Command::Link {
+            addon: addon.to_string(),
+            folder: None,
+        };
+        run(&store, &events, link("subaddon")).unwrap();
+        print_dir_tree(addons_dir.to_str().unwrap());
+        assert!(symlink::is_addon_symlinked(addons_dir.join("SubAddon")));
+
+        run(
+            &store,
+            &events,
+            Command::Unlink {
+                addon: "SubAddon".to_string(),
+                folder: Some(path),
+            },
+        )
+        .unwrap();
+        assert!(!addons_dir.join("SubAddon").exists());
+        let config = store.load().unwrap();
+        assert!(!config.folders[0].repos[0].addons["SubAddon"].enabled);
+
+        let error = run(&store, &events, link("Missing")).unwrap_err();
+        assert_eq!(error.code, EXIT_NOT_FOUND);
+
+        let history = events.history.as_ref().unwrap();
+        let records = history.query(&Default::default());
+        assert_eq!(
+            records.len(),
+            2,
+            "Expected link and unlink, found: {records:?}"
+        );
+        assert!(
+            records
+                .iter()
+                .all(|r| r.status == crate::operation_history::OperationStatus::Completed)
+        );
+    }
+
+    #[test]
+    fn test_stream_lines_are_tagged() {
+        let payload = OperationEventPayload {
+            key: OperationKey {
+                repo_url: "https://github.com/owner/Repo.git".to_string(),
+                folder_path: "/AddOns".to_string(),
+            },
+            event: OperationEvent::Status("Cloning repository...".to_string()),
+        };
+        let event = serde_json::to_value(StreamLine::Event(&payload)).unwrap();
+        println!("{event}");
+        assert_eq!(event["type"], "event");
+        assert_eq!(event["key"]["folderPath"], "/AddOns");
+        assert_eq!(event["event"]["status"], "Cloning repository...");
+
+        let result = serde_json::to_value(StreamLine::Result {
+            data: &Output::None,
+        })
+        .unwrap();
+        assert_eq!(
+            result,
+            serde_json::json!({ "type": "result", "data": null })
+        );
+
+        let error = CliError::new(EXIT_NOT_FOUND, "Repository not found: Repo");
+        let error = serde_json::to_value(StreamLine::Error(&error)).unwrap();
+        assert_eq!(error["type"], "error");
+        assert_eq!(error["code"], EXIT_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_parse_import_uses_directory_headers() {
+        let text = "\
+# Directory: /games/wow/Interface/AddOns
+# <addonName> *<gitUrl> <branch>
+AtlasLoot *https://github.com/owner/AtlasLoot.git main
+
+// comment
+/legacy/Interface/AddOns Questie *https://github.com/owner/Questie.git dev
+Broken https://github.com/owner/Broken.git main
+Deadly Boss Mods *https://github.com/owner/DBM.git main
+C:\\Games\\WoW 335\\Interface\\AddOns Bagnon Bags *https://github.com/owner/Bagnon.git main
+";
+        let entries = parse_import(text);
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    3,
+                    Ok(ImportEntry {
+                        folder: Some("/games/wow/Interface/AddOns".to_string()),
+                        url: "https://github.com/owner/AtlasLoot.git".to_string(),
+                        branch: "main".to_string(),
+                    })
+                ),
+                (
+                    6,
+                    Ok(ImportEntry {
+                        folder: Some("/legacy/Interface/AddOns".to_string()),
+                        url: "https://github.com/owner/Questie.git".to_string(),
+                        branch: "dev".to_string(),
+                    })
+                ),
+                (7, Err("No git URL found (missing *)".to_string())),
+                (
+                    8,
+                    Ok(ImportEntry {
+                        folder: Some("/games/wow/Interface/AddOns".to_string()),
+                        url: "https://github.com/owner/DBM.git".to_string(),
+                        branch: "main".to_string(),
+                    })
+                ),
+                (
+                    9,
+                    Ok(ImportEntry {
+                        folder: Some("C:\\Games\\WoW 335\\Interface\\AddOns".to_string()),
+                        url: "https://github.com/owner/Bagnon.git".to_string(),
+                        branch: "main".to_string(),
+                    })
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_round_trips_through_import() {
+        let folder = DiskAddOnsFolder {
+            path: "/games/wow/Interface/AddOns".to_string(),
+            is_valid: true,
+            repositories: vec![DiskAddonRepository {
+                source: DiskAddonSource::Git {
+                    repo_url: "https://github.com/owner/Repo.git".to_string(),
+                    owner: "owner".to_string(),
+                    repo_name: "Repo".to_string(),
+                    current_branch: Some("dev".to_string()),
+                    available_branches: Vec::new(),
+                    repo_ref: None,
+                    latest_ref: None,
+                    readme: None,
+                },
+                addons: Vec::new(),
+            }],
+            error: None,
+            orphans: Default::default(),
+        };
+        let text = export_text(&[folder]);
+        println!("{text}");
+
+        let entries = parse_import(&text);
+        assert_eq!(entries.len(), 1);
+        let entry = entries[0].1.as_ref().unwrap();
+        assert_eq!(entry.folder.as_deref(), Some("/games/wow/Interface/AddOns"));
+        assert_eq!(entry.url, "https://github.com/owner/Repo.git");
+        assert_eq!(entry.branch, "dev");
+    }
+}
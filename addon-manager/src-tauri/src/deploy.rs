@@ -0,0 +1,469 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    addon_store::{self, ConfigStore},
+    error::{AddonError, AddonErrorKind},
+    game_process,
+    lock::OperationLock,
+    symlink::{self, LinkStyle},
+};
+
+/// Directory in `.addonmanager` holding one manifest per copied or hardlinked addon
+const DEPLOYMENTS_DIR: &str = ".deployments";
+
+/// How sub-addons are made visible in the AddOns folder
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS, Default, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkStrategy {
+    #[default]
+    Symlink,
+    /// Mirror the directory tree and hardlink every file, copying where hardlinks fail
+    HardlinkTree,
+    /// Plain copies, for filesystems without link support such as exFAT or network shares
+    Copy,
+}
+
+impl LinkStrategy {
+    /// What a sub-addon placed with this strategy is called in messages
+    pub fn noun(self) -> &'static str {
+        match self {
+            LinkStrategy::Symlink => "symlink",
+            LinkStrategy::HardlinkTree => "hardlinked copy",
+            LinkStrategy::Copy => "copy",
+        }
+    }
+}
+
+/// Link strategy of a folder together with the global link style
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinkOptions {
+    pub strategy: LinkStrategy,
+    pub style: LinkStyle,
+}
+
+/// Files placed into the AddOns folder for a copied or hardlinked sub-addon
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployManifest {
+    /// Directory name in the AddOns folder
+    pub name: String,
+    /// Sub-addon directory relative to `.addonmanager`
+    pub source: String,
+    pub strategy: LinkStrategy,
+    /// Placed files relative to the addon directory, `/` separated
+    pub files: Vec<String>,
+    /// Size and modification time of each source file when it was placed
+    #[serde(default)]
+    pub sources: BTreeMap<String, SourceStamp>,
+}
+
+/// Identifies the version of a source file without reading it. Copies and hardlinks
+/// keep the modification time of their source on some platforms, so the placed file
+/// itself cannot tell whether it is current.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SourceStamp {
+    pub len: u64,
+    pub modified: SystemTime,
+}
+
+impl SourceStamp {
+    fn of(metadata: &fs::Metadata) -> Option<Self> {
+        Some(SourceStamp {
+            len: metadata.len(),
+            modified: metadata.modified().ok()?,
+        })
+    }
+}
+
+/// Files changed by `sync_deployment`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SyncStats {
+    pub placed: usize,
+    pub removed: usize,
+}
+
+fn manifest_path(addons_dir: &Path, name: &str) -> PathBuf {
+    addons_dir
+        .join(".addonmanager")
+        .join(DEPLOYMENTS_DIR)
+        .join(format!("{name}.json"))
+}
+
+pub fn read_manifest(addons_dir: &Path, name: &str) -> Option<DeployManifest> {
+    let content = fs::read_to_string(manifest_path(addons_dir, name)).ok()?;
+    serde_json::from_str(&content)
+        .map_err(|e| eprintln!("Skipping unreadable deploy manifest for {name}: {e}"))
+        .ok()
+}
+
+fn write_manifest(addons_dir: &Path, manifest: &DeployManifest) -> Result<(), AddonError> {
+    let path = manifest_path(addons_dir, &manifest.name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            AddonError::io("Failed to create deployments dir", &e).with_path(parent)
+        })?;
+    }
+    let content = serde_json::to_string_pretty(manifest).map_err(|e| {
+        AddonError::new(AddonErrorKind::Other, "Failed to serialize deploy manifest").with_cause(e)
+    })?;
+    fs::write(&path, content)
+        .map_err(|e| AddonError::io("Failed to write deploy manifest", &e).with_path(&path))
+}
+
+/// Manifests of every copied or hardlinked sub-addon in the folder
+pub fn list_deployments(addons_dir: &Path) -> Vec<DeployManifest> {
+    let Ok(entries) = fs::read_dir(addons_dir.join(".addonmanager").join(DEPLOYMENTS_DIR)) else {
+        return Vec::new();
+    };
+    let mut manifests: Vec<DeployManifest> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.path().file_stem()?.to_string_lossy().to_string();
+            read_manifest(addons_dir, &name)
+        })
+        .collect();
+    manifests.sort_by(|a, b| a.name.cmp(&b.name));
+    manifests
+}
+
+/// Repository directory name a manifest was deployed from
+pub fn manifest_repo(manifest: &DeployManifest) -> &str {
+    manifest.source.split('/').next().unwrap_or_default()
+}
+
+/// Whether a sub-addon is present in the AddOns folder, as a link or a deployed copy
+pub fn is_linked(addons_dir: &Path, name: &str) -> bool {
+    symlink::is_addon_symlinked(addons_dir.join(name)) || manifest_path(addons_dir, name).is_file()
+}
+
+/// Configured link options for an AddOns folder
+pub fn configured_link_options(store: &impl ConfigStore, folder_path: &str) -> LinkOptions {
+    let config = addon_store::load_user_config(store).unwrap_or_default();
+    LinkOptions {
+        strategy: config
+            .folders
+            .iter()
+            .find(|folder| folder.path == folder_path)
+            .and_then(|folder| folder.settings.link_strategy)
+            .unwrap_or_default(),
+        style: config.link_style.unwrap_or_default(),
+    }
+}
+
+/// Files below `root` relative to it, skipping `.git`
+fn source_files(root: &Path) -> std::io::Result<Vec<String>> {
+    fn walk(root: &Path, dir: &Path, files: &mut Vec<String>) -> std::io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.file_name().is_some_and(|n| n == ".git") {
+                continue;
+            }
+            if path.is_dir() {
+                walk(root, &path, files)?;
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                let parts: Vec<String> = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .collect();
+                files.push(parts.join("/"));
+            }
+        }
+        Ok(())
+    }
+    let mut files = Vec::new();
+    walk(root, root, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// The placed file still exists and its source is unchanged since it was placed.
+/// Manifests written before stamps were recorded place every file once more.
+fn is_up_to_date(stamp: Option<SourceStamp>, dest: &Path, placed: Option<&SourceStamp>) -> bool {
+    let (Some(stamp), Some(placed)) = (stamp, placed) else {
+        return false;
+    };
+    stamp == *placed && fs::metadata(dest).is_ok_and(|dst| dst.len() == stamp.len)
+}
+
+fn place_file(source: &Path, dest: &Path, strategy: LinkStrategy) -> std::io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if dest.exists() {
+        fs::remove_file(dest)?;
+    }
+    if strategy == LinkStrategy::HardlinkTree && fs::hard_link(source, dest).is_ok() {
+        return Ok(());
+    }
+    fs::copy(source, dest).map(|_| ())
+}
+
+/// Remove a placed file and any directories left empty, up to the addon directory
+fn remove_file_and_empty_parents(addon_dir: &Path, relative: &str) {
+    let path = addon_dir.join(relative);
+    if let Err(e) = fs::remove_file(&path)
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        eprintln!("Failed to remove {}: {e}", path.display());
+    }
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == addon_dir || fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+/// Bring a deployed sub-addon in line with its source: place new and changed files
+/// and remove files that no longer exist in the source
+pub fn sync_deployment(
+    addons_dir: &Path,
+    manifest: &DeployManifest,
+) -> Result<SyncStats, AddonError> {
+    let source_dir = addons_dir.join(".addonmanager").join(&manifest.source);
+    let addon_dir = addons_dir.join(&manifest.name);
+    let files = source_files(&source_dir)
+        .map_err(|e| AddonError::io("Failed to read addon source", &e).with_path(&source_dir))?;
+
+    let mut stats = SyncStats::default();
+    let mut sources = BTreeMap::new();
+    for file in &files {
+        let (source, dest) = (source_dir.join(file), addon_dir.join(file));
+        let stamp = fs::metadata(&source)
+            .ok()
+            .and_then(|metadata| SourceStamp::of(&metadata));
+        if let Some(stamp) = stamp {
+            sources.insert(file.clone(), stamp);
+        }
+        if is_up_to_date(stamp, &dest, manifest.sources.get(file)) {
+            continue;
+        }
+        place_file(&source, &dest, manifest.strategy)
+            .map_err(|e| AddonError::io("Failed to place addon file", &e).with_path(&dest))?;
+        stats.placed += 1;
+    }
+    for stale in manifest.files.iter().filter(|f| !files.contains(f)) {
+        remove_file_and_empty_parents(&addon_dir, stale);
+        stats.removed += 1;
+    }
+
+    write_manifest(
+        addons_dir,
+        &DeployManifest {
+            files,
+            sources,
+            ..manifest.clone()
+        },
+    )?;
+    Ok(stats)
+}
+
+/// Copy or hardlink a sub-addon into the AddOns folder and record what was placed.
+/// `target` must be inside `.addonmanager`; `dest` must not exist yet.
+pub fn deploy_addon(
+    addons_dir: &Path,
+    target: &Path,
+    dest: &Path,
+    strategy: LinkStrategy,
+) -> Result<SyncStats, AddonError> {
+    let source = target
+        .strip_prefix(addons_dir.join(".addonmanager"))
+        .map_err(|_| {
+            AddonError::new(
+                AddonErrorKind::InvalidInput,
+                "Addon source is not in .addonmanager",
+            )
+            .with_path(target)
+        })?;
+    let manifest = DeployManifest {
+        name: dest
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        source: source
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/"),
+        strategy,
+        files: Vec::new(),
+        sources: BTreeMap::new(),
+    };
+    fs::create_dir_all(dest)
+        .map_err(|e| AddonError::io("Failed to create addon dir", &e).with_path(dest))?;
+    sync_deployment(addons_dir, &manifest)
+}
+
+/// Remove exactly the files a deployment placed, then its manifest.
+/// Returns false when `name` was not deployed.
+pub fn remove_deployment(addons_dir: &Path, name: &str) -> Result<bool, AddonError> {
+    let Some(manifest) = read_manifest(addons_dir, name) else {
+        return Ok(false);
+    };
+    let addon_dir = addons_dir.join(&manifest.name);
+    for file in &manifest.files {
+        remove_file_and_empty_parents(&addon_dir, file);
+    }
+    // Left in place if the user added files of their own
+    let _ = fs::remove_dir(&addon_dir);
+    let path = manifest_path(addons_dir, name);
+    fs::remove_file(&path)
+        .map_err(|e| AddonError::io("Failed to remove deploy manifest", &e).with_path(&path))?;
+    Ok(true)
+}
+
+/// Make a sub-addon available in the AddOns folder with the given options
+pub fn link_addon(
+    addons_dir: &Path,
+    target: &Path,
+    link: &Path,
+    options: LinkOptions,
+) -> Result<(), AddonError> {
+    match options.strategy {
+        LinkStrategy::Symlink => symlink::create_addon_link(target, link, options.style)
+            .map_err(|e| AddonError::io("Failed to create symlink", &e).with_path(link)),
+        strategy => deploy_addon(addons_dir, target, link, strategy).map(|_| ()),
+    }
+}
+
+/// Remove a sub-addon from the AddOns folder, whether linked or deployed.
+/// Directories that are neither are left alone.
+pub fn unlink_addon(addons_dir: &Path, name: &str) -> Result<(), AddonError> {
+    game_process::ensure_not_running(addons_dir, "unlinking addons", &mut |_| {})?;
+    let _folder_lock = OperationLock::acquire_folder(addons_dir, "unlink")?;
+    if remove_deployment(addons_dir, name)? {
+        return Ok(());
+    }
+    let link = addons_dir.join(name);
+    if symlink::is_addon_symlinked(&link) {
+        fs::remove_file(&link)
+            .or_else(|_| fs::remove_dir(&link))
+            .map_err(|e| AddonError::io("Failed to remove symlink", &e).with_path(&link))?;
+    }
+    Ok(())
+}
+
+/// Re-sync every deployment of a repository after it changed on disk
+pub fn sync_repo_deployments(addons_dir: &Path, repo_name: &str) -> Result<SyncStats, AddonError> {
+    let mut total = SyncStats::default();
+    for manifest in list_deployments(addons_dir)
+        .iter()
+        .filter(|m| manifest_repo(m) == repo_name)
+    {
+        let source = addons_dir.join(".addonmanager").join(&manifest.source);
+        if !source.is_dir() {
+            // The sub-addon was removed upstream
+            remove_deployment(addons_dir, &manifest.name)?;
+            total.removed += manifest.files.len();
+            continue;
+        }
+        let stats = sync_deployment(addons_dir, manifest)?;
+        total.placed += stats.placed;
+        total.removed += stats.removed;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{print_dir_tree, setup_addons_dir};
+    use crate::validate;
+
+    #[test]
+    fn test_copy_deploy_sync_and_remove() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let source = validate::ensure_manager_dir(&addons_dir)
+            .unwrap()
+            .join("FakeRepo/FakeAddon");
+        fs::create_dir_all(source.join("Libs")).unwrap();
+        fs::write(source.join("FakeAddon.toc"), "## Title: Fake").unwrap();
+        fs::write(source.join("Libs/Lib.lua"), "-- lib").unwrap();
+        let dest = addons_dir.join("FakeAddon");
+
+        let stats = deploy_addon(&addons_dir, &source, &dest, LinkStrategy::Copy).unwrap();
+        assert_eq!(stats.placed, 2);
+        assert!(!dest.is_symlink() && dest.join("Libs/Lib.lua").is_file());
+        assert!(is_linked(&addons_dir, "FakeAddon"));
+
+        // Simulate an update: one file removed, one added, one changed
+        fs::remove_file(source.join("Libs/Lib.lua")).unwrap();
+        fs::write(source.join("New.lua"), "-- new").unwrap();
+        fs::write(source.join("FakeAddon.toc"), "## Title: Fake v2").unwrap();
+        let stats = sync_repo_deployments(&addons_dir, "FakeRepo").unwrap();
+        print_dir_tree(addons_dir.to_str().unwrap());
+        assert_eq!(
+            stats,
+            SyncStats {
+                placed: 2,
+                removed: 1
+            }
+        );
+        assert!(
+            !dest.join("Libs").exists(),
+            "Emptied directories are removed"
+        );
+        assert_eq!(
+            fs::read_to_string(dest.join("FakeAddon.toc")).unwrap(),
+            "## Title: Fake v2"
+        );
+
+        // Files the user added themselves survive removal
+        fs::write(dest.join("user.txt"), "mine").unwrap();
+        unlink_addon(&addons_dir, "FakeAddon").unwrap();
+        assert!(dest.join("user.txt").exists());
+        assert!(!dest.join("New.lua").exists());
+        assert!(read_manifest(&addons_dir, "FakeAddon").is_none());
+    }
+
+    #[test]
+    fn test_sync_skips_copies_with_source_mtime() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let source = validate::ensure_manager_dir(&addons_dir)
+            .unwrap()
+            .join("FakeRepo/FakeAddon");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("FakeAddon.toc"), "## Title: Fake").unwrap();
+        let dest = addons_dir.join("FakeAddon");
+        deploy_addon(&addons_dir, &source, &dest, LinkStrategy::Copy).unwrap();
+
+        // Copies on Windows keep the modification time of their source
+        let modified = fs::metadata(source.join("FakeAddon.toc"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        fs::File::options()
+            .write(true)
+            .open(dest.join("FakeAddon.toc"))
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let stats = sync_repo_deployments(&addons_dir, "FakeRepo").unwrap();
+        assert_eq!(stats, SyncStats::default(), "Unchanged files stay in place");
+
+        // Same size, new content
+        fs::write(source.join("FakeAddon.toc"), "## Title: Fak2").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(source.join("FakeAddon.toc"))
+            .unwrap()
+            .set_modified(modified + std::time::Duration::from_secs(1))
+            .unwrap();
+        let stats = sync_repo_deployments(&addons_dir, "FakeRepo").unwrap();
+        assert_eq!(stats.placed, 1);
+        assert_eq!(
+            fs::read_to_string(dest.join("FakeAddon.toc")).unwrap(),
+            "## Title: Fak2"
+        );
+    }
+}
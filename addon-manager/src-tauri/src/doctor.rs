@@ -0,0 +1,395 @@
+use std::{ffi::OsStr, fmt, fs, path::Path};
+
+use git2::Repository;
+use serde::Serialize;
+use tauri::AppHandle;
+use ts_rs::TS;
+
+use crate::{
+    addon_disk,
+    addon_store::{self, ConfigStore},
+    error::AddonError,
+    git, orphans, validate,
+};
+
+#[derive(Debug, Serialize, Clone, Copy, TS, PartialEq, Eq, PartialOrd, Ord)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueSeverity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    /// The folder does not exist or is not an `Interface/AddOns` folder
+    InvalidFolder,
+    /// `Repository::open` failed on a directory with a `.git` folder
+    UnreadableRepo,
+    /// Objects in the repository could not be read or failed hash verification
+    CorruptObjects,
+    /// The repository has no `origin` remote
+    MissingOrigin,
+    /// `origin` points to a repository with a different name than the directory
+    OriginMismatch,
+    /// HEAD is not on a branch
+    DetachedHead,
+    /// A link in the AddOns folder points to a missing target
+    BrokenLink,
+    /// A TOC file could not be read
+    UnreadableToc,
+}
+
+/// A single problem found by `diagnose_folder`
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorIssue {
+    pub severity: IssueSeverity,
+    pub kind: IssueKind,
+    /// Repository directory name in `.addonmanager`, if the issue belongs to one
+    pub repo: Option<String>,
+    pub path: String,
+    pub message: String,
+    /// Suggested way to fix the issue
+    pub fix: String,
+}
+
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderDiagnosis {
+    pub path: String,
+    pub issues: Vec<DoctorIssue>,
+}
+
+/// Health of every configured AddOns folder
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorReport {
+    pub folders: Vec<FolderDiagnosis>,
+}
+
+impl DoctorReport {
+    pub fn has_errors(&self) -> bool {
+        self.folders
+            .iter()
+            .flat_map(|folder| &folder.issues)
+            .any(|issue| issue.severity == IssueSeverity::Error)
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, folder) in self.folders.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", folder.path)?;
+            if folder.issues.is_empty() {
+                write!(f, "\n  ok")?;
+            }
+            for issue in &folder.issues {
+                let repo = issue
+                    .repo
+                    .as_ref()
+                    .map(|repo| format!("[{repo}] "))
+                    .unwrap_or_default();
+                write!(
+                    f,
+                    "\n  {:?}: {repo}{}\n    path: {}\n    fix: {}",
+                    issue.severity, issue.message, issue.path, issue.fix
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn issue(
+    severity: IssueSeverity,
+    kind: IssueKind,
+    repo: Option<&str>,
+    path: &Path,
+    message: impl Into<String>,
+    fix: impl Into<String>,
+) -> DoctorIssue {
+    DoctorIssue {
+        severity,
+        kind,
+        repo: repo.map(str::to_string),
+        path: path.to_string_lossy().to_string(),
+        message: message.into(),
+        fix: fix.into(),
+    }
+}
+
+/// Read every object in the object database; libgit2 verifies each hash on read
+fn check_objects(repo: &Repository) -> Result<(), String> {
+    let odb = repo.odb().map_err(|e| e.message().to_string())?;
+    let mut failure = None;
+    odb.foreach(|oid| match odb.read(*oid) {
+        Ok(_) => true,
+        Err(e) => {
+            failure = Some(format!("{oid}: {}", e.message()));
+            false
+        }
+    })
+    .map_err(|e| failure.clone().unwrap_or_else(|| e.message().to_string()))?;
+    if let Some(failure) = failure {
+        return Err(failure);
+    }
+    repo.head()
+        .and_then(|head| head.peel_to_tree())
+        .map(|_| ())
+        .map_err(|e| e.message().to_string())
+}
+
+fn diagnose_git_repo(repo_dir: &Path, name: &str, issues: &mut Vec<DoctorIssue>) {
+    let repo = match Repository::open(repo_dir) {
+        Ok(repo) => repo,
+        Err(e) => {
+            issues.push(issue(
+                IssueSeverity::Error,
+                IssueKind::UnreadableRepo,
+                Some(name),
+                repo_dir,
+                format!("Failed to open repository: {}", e.message()),
+                "Delete the addon and install it again",
+            ));
+            return;
+        }
+    };
+
+    if let Err(e) = check_objects(&repo) {
+        issues.push(issue(
+            IssueSeverity::Error,
+            IssueKind::CorruptObjects,
+            Some(name),
+            repo_dir,
+            format!("Repository objects are corrupt: {e}"),
+            "Delete the addon and install it again",
+        ));
+    }
+
+    let origin = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|remote| remote.url().map(str::to_string));
+    match origin {
+        None => issues.push(issue(
+            IssueSeverity::Warning,
+            IssueKind::MissingOrigin,
+            Some(name),
+            repo_dir,
+            "Repository has no origin remote and cannot be updated",
+            "Install the addon from its repository URL",
+        )),
+        Some(url) => match git::extract_owner_repo_from_url(&url) {
+            Ok((_, expected)) if expected == name => {}
+            Ok((_, expected)) => issues.push(issue(
+                IssueSeverity::Warning,
+                IssueKind::OriginMismatch,
+                Some(name),
+                repo_dir,
+                format!("Origin {url} belongs to '{expected}', not '{name}'"),
+                format!("Rename the directory to '{expected}' or reinstall from the correct URL"),
+            )),
+            Err(e) => issues.push(issue(
+                IssueSeverity::Warning,
+                IssueKind::OriginMismatch,
+                Some(name),
+                repo_dir,
+                format!("Origin {url} is not a supported repository URL: {e}"),
+                "Reinstall the addon from a supported repository URL",
+            )),
+        },
+    }
+
+    if repo.head_detached().unwrap_or(false) {
+        issues.push(issue(
+            IssueSeverity::Warning,
+            IssueKind::DetachedHead,
+            Some(name),
+            repo_dir,
+            "HEAD is detached and will not follow updates",
+            "Select a branch for the addon",
+        ));
+    }
+}
+
+fn diagnose_tocs(repo_dir: &Path, name: &str, issues: &mut Vec<DoctorIssue>) {
+    let Ok(addons) = addon_disk::find_all_sub_addons(&repo_dir.to_path_buf()) else {
+        return;
+    };
+    for addon in addons {
+        let addon_dir = if addon.dir == "." {
+            repo_dir.to_path_buf()
+        } else {
+            repo_dir.join(&addon.dir)
+        };
+        let Ok(entries) = fs::read_dir(&addon_dir) else {
+            continue;
+        };
+        for toc in entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension() == Some(OsStr::new("toc")))
+        {
+            if let Err(e) = fs::read_to_string(&toc) {
+                issues.push(issue(
+                    IssueSeverity::Warning,
+                    IssueKind::UnreadableToc,
+                    Some(name),
+                    &toc,
+                    format!("Failed to read TOC file: {e}"),
+                    "Check file permissions and encoding, or reinstall the addon",
+                ));
+            }
+        }
+    }
+}
+
+/// Check a single AddOns folder and the repositories in its `.addonmanager`
+pub fn diagnose_folder(addons_dir: &Path) -> FolderDiagnosis {
+    let mut issues = Vec::new();
+    if !addons_dir.is_dir() || !validate::is_valid_addons_folder(addons_dir) {
+        issues.push(issue(
+            IssueSeverity::Error,
+            IssueKind::InvalidFolder,
+            None,
+            addons_dir,
+            "Not an existing Interface/AddOns folder",
+            "Remove the folder and add the game's Interface/AddOns folder instead",
+        ));
+    }
+
+    let manager_dir = addons_dir.join(".addonmanager");
+    let mut repo_dirs: Vec<_> = fs::read_dir(&manager_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_dir()
+                        && !path
+                            .file_name()
+                            .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    repo_dirs.sort();
+
+    for repo_dir in repo_dirs {
+        let name = repo_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if repo_dir.join(".git").exists() {
+            diagnose_git_repo(&repo_dir, &name, &mut issues);
+        }
+        diagnose_tocs(&repo_dir, &name, &mut issues);
+    }
+
+    for link in orphans::scan_orphans(addons_dir).broken_links {
+        issues.push(issue(
+            IssueSeverity::Error,
+            IssueKind::BrokenLink,
+            None,
+            &addons_dir.join(&link.name),
+            format!("Link points to missing {}", link.target),
+            "Run cleanup to remove broken links",
+        ));
+    }
+
+    FolderDiagnosis {
+        path: addons_dir.to_string_lossy().to_string(),
+        issues,
+    }
+}
+
+/// Check every configured AddOns folder
+pub fn diagnose(store: &impl ConfigStore) -> Result<DoctorReport, AddonError> {
+    let config = addon_store::load_user_config(store)?;
+    Ok(DoctorReport {
+        folders: config
+            .folders
+            .iter()
+            .map(|folder| diagnose_folder(Path::new(&folder.path)))
+            .collect(),
+    })
+}
+
+#[tauri::command]
+pub async fn doctor_cmd(app_handle: AppHandle) -> Result<DoctorReport, String> {
+    tauri::async_runtime::spawn_blocking(move || diagnose(&app_handle))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+        .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symlink;
+    use crate::test_utils::{print_dir_tree, setup_addons_dir};
+
+    #[test]
+    fn test_diagnose_folder_reports_issues() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let manager_dir = validate::ensure_manager_dir(&addons_dir).unwrap();
+
+        let repo_dir = manager_dir.join("FakeRepo");
+        fs::create_dir_all(repo_dir.join("FakeAddon")).unwrap();
+        fs::write(
+            repo_dir.join("FakeAddon").join("FakeAddon.toc"),
+            "## Title: Fake",
+        )
+        .unwrap();
+        let repo = Repository::init(&repo_dir).unwrap();
+        repo.remote("origin", "https://github.com/owner/OtherRepo.git")
+            .unwrap();
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        let commit = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        repo.set_head_detached(commit).unwrap();
+
+        symlink::create_symlink(manager_dir.join("Missing"), addons_dir.join("Missing")).unwrap();
+        print_dir_tree(addons_dir.to_str().unwrap());
+
+        let diagnosis = diagnose_folder(&addons_dir);
+        let report = DoctorReport {
+            folders: vec![diagnosis.clone()],
+        };
+        println!("{report}");
+
+        let kinds: Vec<IssueKind> = diagnosis.issues.iter().map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                IssueKind::OriginMismatch,
+                IssueKind::DetachedHead,
+                IssueKind::BrokenLink
+            ]
+        );
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn test_diagnose_invalid_folder() {
+        let temp = tempfile::tempdir().unwrap();
+        let diagnosis = diagnose_folder(temp.path());
+        assert_eq!(diagnosis.issues.len(), 1);
+        assert_eq!(diagnosis.issues[0].kind, IssueKind::InvalidFolder);
+    }
+}
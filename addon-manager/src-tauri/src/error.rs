@@ -0,0 +1,177 @@
+use std::{fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Machine-readable error category so the UI and CLI can react per kind
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum AddonErrorKind {
+    /// Remote could not be reached: DNS, connection, TLS or HTTP failures
+    Network,
+    /// Repository, branch, file or folder does not exist
+    NotFound,
+    /// Filesystem permission or remote authentication failure
+    PermissionDenied,
+    /// Malformed repo URL, path or argument
+    InvalidInput,
+    /// Another operation holds the lock
+    Locked,
+    /// The game client is running from the installation of the AddOns folder
+    GameRunning,
+    /// The operation was cancelled by the user
+    Cancelled,
+    /// Any other git failure
+    Git,
+    /// Any other filesystem failure
+    Io,
+    /// The user config store could not be read or written
+    Config,
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct AddonError {
+    pub kind: AddonErrorKind,
+    /// Human readable summary of what failed
+    pub message: String,
+    /// Affected file or directory
+    pub path: Option<String>,
+    /// Affected repository URL
+    pub url: Option<String>,
+    /// Underlying error message
+    pub cause: Option<String>,
+}
+
+impl AddonError {
+    pub fn new<S: Into<String>>(kind: AddonErrorKind, message: S) -> Self {
+        AddonError {
+            kind,
+            message: message.into(),
+            path: None,
+            url: None,
+            cause: None,
+        }
+    }
+
+    /// Build from an io error, classifying by its kind
+    pub fn io<S: Into<String>>(message: S, err: &std::io::Error) -> Self {
+        let kind = match err.kind() {
+            std::io::ErrorKind::NotFound => AddonErrorKind::NotFound,
+            std::io::ErrorKind::PermissionDenied => AddonErrorKind::PermissionDenied,
+            _ => AddonErrorKind::Io,
+        };
+        Self::new(kind, message).with_cause(err)
+    }
+
+    /// Build from a git2 error, classifying by its class and code
+    pub fn git<S: Into<String>>(message: S, err: &git2::Error) -> Self {
+        let kind = match (err.code(), err.class()) {
+            (git2::ErrorCode::NotFound, _) => AddonErrorKind::NotFound,
+            (git2::ErrorCode::Auth, _) => AddonErrorKind::PermissionDenied,
+            (git2::ErrorCode::Locked, _) => AddonErrorKind::Locked,
+            (git2::ErrorCode::User, _) => AddonErrorKind::Cancelled,
+            (git2::ErrorCode::Certificate, _)
+            | (_, git2::ErrorClass::Net)
+            | (_, git2::ErrorClass::Http)
+            | (_, git2::ErrorClass::Ssl)
+            | (_, git2::ErrorClass::Ssh) => AddonErrorKind::Network,
+            (_, git2::ErrorClass::Os) => AddonErrorKind::Io,
+            _ => AddonErrorKind::Git,
+        };
+        Self::new(kind, message).with_cause(err.message())
+    }
+
+    pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.path = Some(path.as_ref().to_string_lossy().to_string());
+        self
+    }
+
+    pub fn with_url<S: Into<String>>(mut self, url: S) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn with_cause<E: fmt::Display>(mut self, cause: E) -> Self {
+        self.cause = Some(cause.to_string());
+        self
+    }
+}
+
+impl fmt::Display for AddonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(path) = &self.path {
+            write!(f, " ({path})")?;
+        }
+        if let Some(cause) = &self.cause {
+            write!(f, ": {cause}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AddonError {}
+
+/// Tauri commands return plain strings to the frontend
+impl From<AddonError> for String {
+    fn from(err: AddonError) -> Self {
+        err.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_error_kind_classification() {
+        let not_found = std::io::Error::from(std::io::ErrorKind::NotFound);
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let other = std::io::Error::other("disk full");
+
+        assert_eq!(
+            AddonError::io("read", &not_found).kind,
+            AddonErrorKind::NotFound
+        );
+        assert_eq!(
+            AddonError::io("read", &denied).kind,
+            AddonErrorKind::PermissionDenied
+        );
+        assert_eq!(AddonError::io("read", &other).kind, AddonErrorKind::Io);
+    }
+
+    #[test]
+    fn test_git_error_kind_classification() {
+        let net = git2::Error::new(
+            git2::ErrorCode::GenericError,
+            git2::ErrorClass::Net,
+            "failed to resolve address for github.com",
+        );
+        let missing = git2::Error::new(
+            git2::ErrorCode::NotFound,
+            git2::ErrorClass::Reference,
+            "reference not found",
+        );
+
+        assert_eq!(AddonError::git("fetch", &net).kind, AddonErrorKind::Network);
+        assert_eq!(
+            AddonError::git("fetch", &missing).kind,
+            AddonErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_display_includes_path_and_cause() {
+        let err = AddonError::new(AddonErrorKind::Io, "Failed to copy folder")
+            .with_path("/AddOns/Foo")
+            .with_cause("disk full");
+        assert_eq!(
+            err.to_string(),
+            "Failed to copy folder (/AddOns/Foo): disk full"
+        );
+    }
+}
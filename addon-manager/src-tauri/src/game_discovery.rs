@@ -0,0 +1,324 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use tauri::AppHandle;
+use ts_rs::TS;
+
+use crate::{
+    addon_store,
+    error::{AddonError, AddonErrorKind},
+    operation_reporter::OperationEvent,
+    validate,
+};
+
+/// Client executables, compared ignoring case
+const CLIENT_EXECUTABLES: &[&str] = &["wow.exe", "wow-64.exe", "wowclassic.exe", "ascension.exe"];
+/// Signature of `VS_FIXEDFILEINFO` in the version resource of an executable
+const FIXED_FILE_INFO_SIGNATURE: [u8; 4] = 0xFEEF04BDu32.to_le_bytes();
+
+/// A game installation with an AddOns folder found on disk
+#[derive(Debug, Serialize, Clone, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct GameInstall {
+    /// The `Interface/AddOns` folder
+    pub addons_path: String,
+    pub game_path: String,
+    /// Client executable in the game directory
+    pub executable: Option<String>,
+    /// File version of the client executable, e.g. `3.3.5.12340`
+    pub client_version: Option<String>,
+    /// Whether the AddOns folder is already managed
+    pub configured: bool,
+}
+
+fn child_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Child of `dir` with the given name ignoring case, game files are often
+/// copied from Windows with a different case
+fn find_child(dir: &Path, name: &str) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .find(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .eq_ignore_ascii_case(name)
+        })
+        .map(|entry| entry.path())
+}
+
+/// Wine prefixes of plain Wine, Lutris, Bottles and Steam Proton under the home directory
+fn wine_prefixes(home: &Path) -> Vec<PathBuf> {
+    let mut prefixes = vec![home.join(".wine")];
+    // Lutris installs every game into its own prefix in ~/Games
+    prefixes.extend(child_dirs(&home.join("Games")));
+    for bottles in [
+        ".local/share/bottles/bottles",
+        ".var/app/com.usebottles.bottles/data/bottles/bottles",
+    ] {
+        prefixes.extend(child_dirs(&home.join(bottles)));
+    }
+    for steam in [".steam/steam", ".local/share/Steam"] {
+        prefixes.extend(
+            child_dirs(&home.join(steam).join("steamapps/compatdata"))
+                .into_iter()
+                .map(|compatdata| compatdata.join("pfx")),
+        );
+    }
+    prefixes.retain(|prefix| prefix.join("drive_c").is_dir());
+    prefixes
+}
+
+/// Directories a game is typically installed in below a drive or home root
+fn game_dir_candidates(root: &Path) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    for parent in ["", "Games", "Program Files", "Program Files (x86)"] {
+        let parent = root.join(parent);
+        for dir in child_dirs(&parent) {
+            // The Ascension launcher keeps one client per realm in resources
+            candidates.extend(child_dirs(&dir.join("resources")));
+            candidates.push(dir);
+        }
+    }
+    candidates
+}
+
+/// The client executable in a game directory
+fn find_executable(game_dir: &Path) -> Option<PathBuf> {
+    let mut executables: Vec<PathBuf> = fs::read_dir(game_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+            CLIENT_EXECUTABLES.contains(&name.as_str())
+        })
+        .map(|entry| entry.path())
+        .collect();
+    executables.sort();
+    executables.into_iter().next()
+}
+
+/// A game directory is recognized by its client executable or its `Data` folder
+pub fn is_game_root(dir: &Path) -> bool {
+    find_executable(dir).is_some() || find_child(dir, "Data").is_some_and(|data| data.is_dir())
+}
+
+/// The AddOns folder to manage for a selected path. Accepts an AddOns folder or
+/// the root of a game installation; `Interface/AddOns` of a game that has none
+/// yet, as after unpacking a fresh client, is created.
+pub fn ensure_addons_folder<F>(path: &Path, mut reporter: F) -> Result<PathBuf, AddonError>
+where
+    F: FnMut(OperationEvent),
+{
+    let (game_dir, addons_dir) = if validate::is_valid_addons_folder(path) {
+        if path.is_dir() {
+            return Ok(path.to_path_buf());
+        }
+        match path.parent().and_then(Path::parent) {
+            Some(game_dir) => (game_dir.to_path_buf(), path.to_path_buf()),
+            None => (path.to_path_buf(), path.to_path_buf()),
+        }
+    } else if is_game_root(path) {
+        let interface = find_child(path, "Interface").unwrap_or_else(|| path.join("Interface"));
+        let addons_dir =
+            find_child(&interface, "AddOns").unwrap_or_else(|| interface.join("AddOns"));
+        (path.to_path_buf(), addons_dir)
+    } else if path.is_dir() {
+        return Ok(path.to_path_buf());
+    } else {
+        (path.to_path_buf(), path.to_path_buf())
+    };
+
+    if addons_dir.is_dir() {
+        return Ok(addons_dir);
+    }
+    if !is_game_root(&game_dir) {
+        return Err(
+            AddonError::new(AddonErrorKind::NotFound, "Directory does not exist").with_path(path),
+        );
+    }
+    for dir in [addons_dir.parent().unwrap_or(&addons_dir), &addons_dir] {
+        if dir.is_dir() {
+            continue;
+        }
+        fs::create_dir(dir)
+            .map_err(|e| AddonError::io("Failed to create AddOns folder", &e).with_path(dir))?;
+        reporter(OperationEvent::Status(format!("Created {}", dir.display())));
+    }
+    Ok(addons_dir)
+}
+
+/// File version from the `VS_FIXEDFILEINFO` of a Windows executable
+fn client_version(executable: &Path) -> Option<String> {
+    let bytes = fs::read(executable).ok()?;
+    let start = bytes
+        .windows(FIXED_FILE_INFO_SIGNATURE.len())
+        .position(|window| window == FIXED_FILE_INFO_SIGNATURE)?;
+    // Signature and struct version are followed by the most and least significant file version
+    let word = |offset: usize| -> Option<u32> {
+        let bytes = bytes.get(start + offset..start + offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    };
+    let (ms, ls) = (word(8)?, word(12)?);
+    Some(format!(
+        "{}.{}.{}.{}",
+        ms >> 16,
+        ms & 0xFFFF,
+        ls >> 16,
+        ls & 0xFFFF
+    ))
+}
+
+/// Client version of the game installation an AddOns folder belongs to
+pub fn detect_client_version(addons_dir: &Path) -> Option<String> {
+    let game_dir = addons_dir.parent()?.parent()?;
+    client_version(&find_executable(game_dir)?)
+}
+
+/// The game installation in `game_dir` if it has a valid AddOns folder
+fn inspect_game_dir(game_dir: &Path) -> Option<GameInstall> {
+    let addons_dir = find_child(&find_child(game_dir, "Interface")?, "AddOns")?;
+    if !addons_dir.is_dir() || !validate::is_valid_addons_folder(&addons_dir) {
+        return None;
+    }
+    let executable = find_executable(game_dir);
+    Some(GameInstall {
+        addons_path: addons_dir.to_string_lossy().to_string(),
+        game_path: game_dir.to_string_lossy().to_string(),
+        client_version: executable.as_deref().and_then(client_version),
+        executable: executable.map(|exe| exe.to_string_lossy().to_string()),
+        configured: false,
+    })
+}
+
+/// Game installations in the common locations below `home`: Wine prefixes of
+/// Wine, Lutris, Bottles and Proton, their Windows install paths and the
+/// Ascension launcher
+pub fn discover_game_installs_in(home: &Path) -> Vec<GameInstall> {
+    let mut roots = vec![home.to_path_buf()];
+    roots.extend(
+        wine_prefixes(home)
+            .into_iter()
+            .map(|prefix| prefix.join("drive_c")),
+    );
+    if cfg!(windows)
+        && let Some(drive) = std::env::var_os("SystemDrive")
+    {
+        roots.push(PathBuf::from(format!("{}\\", drive.to_string_lossy())));
+    }
+
+    let mut installs: Vec<GameInstall> = roots
+        .iter()
+        .flat_map(|root| game_dir_candidates(root))
+        .filter_map(|game_dir| inspect_game_dir(&game_dir))
+        .collect();
+    installs.sort_by(|a, b| a.addons_path.cmp(&b.addons_path));
+    installs.dedup_by(|a, b| a.addons_path == b.addons_path);
+    installs
+}
+
+/// Game installations below the home directory of the current user
+pub fn discover_game_installs() -> Vec<GameInstall> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| discover_game_installs_in(Path::new(&home)))
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn discover_game_installs_cmd(app_handle: AppHandle) -> Result<Vec<GameInstall>, String> {
+    let config = addon_store::load_user_config(&app_handle)?;
+    let mut installs = tauri::async_runtime::spawn_blocking(discover_game_installs)
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?;
+    for install in &mut installs {
+        install.configured = config.folders.iter().any(|f| f.path == install.addons_path);
+    }
+    Ok(installs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_installs_in_prefixes() {
+        let home = tempfile::tempdir().unwrap();
+        let ascension = home.path().join(
+            "Games/ascension-wow/drive_c/Program Files/Ascension Launcher/resources/epoch_live",
+        );
+        let proton = home
+            .path()
+            .join(".local/share/Steam/steamapps/compatdata/123/pfx/drive_c/Games/WoW335");
+        let no_addons = home.path().join(".wine/drive_c/Games/Empty");
+        fs::create_dir_all(ascension.join("Interface/Addons")).unwrap();
+        fs::create_dir_all(proton.join("Interface/AddOns")).unwrap();
+        fs::create_dir_all(&no_addons).unwrap();
+
+        // Version resource of 3.3.5.12340 after some padding
+        let mut exe = vec![0u8; 64];
+        exe.extend(FIXED_FILE_INFO_SIGNATURE);
+        exe.extend(0x0001_0000u32.to_le_bytes());
+        exe.extend(((3u32 << 16) | 3).to_le_bytes());
+        exe.extend(((5u32 << 16) | 12340).to_le_bytes());
+        fs::write(proton.join("Wow.exe"), exe).unwrap();
+
+        let installs = discover_game_installs_in(home.path());
+        println!("{installs:#?}");
+        assert_eq!(installs.len(), 2);
+        assert_eq!(
+            installs[0].game_path,
+            proton.to_string_lossy().to_string(),
+            "Sorted by path, .local comes before Games"
+        );
+        assert_eq!(installs[0].client_version.as_deref(), Some("3.3.5.12340"));
+        assert!(
+            installs[1]
+                .addons_path
+                .ends_with("epoch_live/Interface/Addons")
+        );
+        assert_eq!(installs[1].executable, None);
+    }
+
+    #[test]
+    fn test_create_addons_folder_in_fresh_client() {
+        let temp = tempfile::tempdir().unwrap();
+        let game = temp.path().join("wow335");
+        fs::create_dir_all(game.join("Data")).unwrap();
+        let mut messages = Vec::new();
+        let mut reporter = |event| {
+            if let OperationEvent::Status(message) = event {
+                messages.push(message)
+            }
+        };
+
+        let addons_dir = ensure_addons_folder(&game, &mut reporter).unwrap();
+        assert_eq!(addons_dir, game.join("Interface/AddOns"));
+        assert!(addons_dir.is_dir());
+
+        assert_eq!(
+            ensure_addons_folder(&game, &mut reporter).unwrap(),
+            addons_dir
+        );
+        let not_a_game = temp.path().join("missing/Interface/AddOns");
+        let error = ensure_addons_folder(&not_a_game, &mut reporter).unwrap_err();
+        assert_eq!(error.kind, AddonErrorKind::NotFound);
+        assert_eq!(messages.len(), 2, "Only Interface and AddOns were created");
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::{AddonError, AddonErrorKind},
+    operation_reporter::OperationEvent,
+};
+
+/// The game installation the AddOns folder belongs to.
+/// The AddOns folder is expected at `<game>/Interface/AddOns`.
+pub fn game_dir(addons_dir: &Path) -> Option<PathBuf> {
+    let game_dir = addons_dir.parent()?.parent()?;
+    Some(fs::canonicalize(game_dir).unwrap_or_else(|_| game_dir.to_path_buf()))
+}
+
+/// A running process as seen in `/proc/<pid>`
+#[derive(Debug, Default, Clone)]
+struct Process {
+    exe: PathBuf,
+    cmdline: Vec<String>,
+    /// `WINEPREFIX` from the environment of the process
+    wine_prefix: Option<PathBuf>,
+}
+
+impl Process {
+    /// Wine runs Windows executables through its loader, the executable is
+    /// then only known from the command line
+    fn is_wine(&self) -> bool {
+        self.exe
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("wine"))
+    }
+
+    /// Paths of the executables the process runs
+    fn executables(&self) -> Vec<PathBuf> {
+        if !self.is_wine() {
+            return vec![self.exe.clone()];
+        }
+        let prefix = self.wine_prefix.clone().or_else(default_wine_prefix);
+        self.cmdline
+            .iter()
+            .filter(|arg| arg.to_ascii_lowercase().ends_with(".exe"))
+            .filter_map(|arg| wine_to_unix_path(arg, prefix.as_deref()))
+            .collect()
+    }
+}
+
+fn default_wine_prefix() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".wine"))
+}
+
+/// Translate a path as Wine passes it on the command line, e.g. `C:\Games\WoW\Wow.exe`,
+/// to the path on the host through the drive links in `<prefix>/dosdevices`
+fn wine_to_unix_path(path: &str, prefix: Option<&Path>) -> Option<PathBuf> {
+    if path.starts_with('/') {
+        return Some(PathBuf::from(path));
+    }
+    let drive = path.chars().next().filter(char::is_ascii_alphabetic)?;
+    let rest = path.strip_prefix(drive)?.strip_prefix(':')?;
+    let drive = drive.to_ascii_lowercase();
+
+    let root = prefix
+        .and_then(|prefix| {
+            fs::canonicalize(prefix.join("dosdevices").join(format!("{drive}:"))).ok()
+        })
+        .or_else(|| match drive {
+            'c' => prefix.map(|prefix| prefix.join("drive_c")),
+            'z' => Some(PathBuf::from("/")),
+            _ => None,
+        })?;
+    Some(
+        rest.split(['\\', '/'])
+            .filter(|part| !part.is_empty())
+            .fold(root, |path, part| path.join(part)),
+    )
+}
+
+/// Windows paths are case insensitive, so the case on the command line of a
+/// Wine process may not match the directory on disk
+fn runs_from(exe: &Path, game_dir: &Path) -> bool {
+    exe.parent().is_some_and(|dir| {
+        let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        dir.to_string_lossy()
+            .eq_ignore_ascii_case(&game_dir.to_string_lossy())
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn read_process(dir: &Path) -> Option<Process> {
+    let exe = fs::read_link(dir.join("exe")).ok()?;
+    let split = |bytes: Vec<u8>| -> Vec<Vec<u8>> {
+        bytes
+            .split(|&b| b == 0)
+            .filter(|part| !part.is_empty())
+            .map(<[u8]>::to_vec)
+            .collect()
+    };
+    let cmdline = fs::read(dir.join("cmdline"))
+        .map(split)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|arg| String::from_utf8_lossy(&arg).into_owned())
+        .collect();
+    // Only readable for processes of the same user, which the game is
+    let wine_prefix = fs::read(dir.join("environ")).ok().and_then(|environ| {
+        split(environ).into_iter().find_map(|var| {
+            var.strip_prefix(b"WINEPREFIX=")
+                .map(|value| PathBuf::from(String::from_utf8_lossy(value).into_owned()))
+        })
+    });
+    Some(Process {
+        exe,
+        cmdline,
+        wine_prefix,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn running_processes() -> Vec<Process> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .chars()
+                .all(|c| c.is_ascii_digit())
+        })
+        .filter_map(|entry| read_process(&entry.path()))
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn running_processes() -> Vec<Process> {
+    Vec::new()
+}
+
+/// Executable of a game client running from the installation the AddOns folder
+/// belongs to, natively or through Wine
+pub fn running_client(addons_dir: &Path) -> Option<PathBuf> {
+    let game_dir = game_dir(addons_dir)?;
+    running_processes()
+        .iter()
+        .flat_map(Process::executables)
+        .find(|exe| runs_from(exe, &game_dir))
+}
+
+/// Whether a game client is running from the installation the AddOns folder belongs to
+pub fn is_game_running(addons_dir: &Path) -> bool {
+    running_client(addons_dir).is_some()
+}
+
+/// Refuse to touch the files of a running game client, it would crash or overwrite
+/// SavedVariables on exit. The user is warned through the reporter.
+pub fn ensure_not_running<F>(
+    addons_dir: &Path,
+    action: &str,
+    reporter: &mut F,
+) -> Result<(), AddonError>
+where
+    F: FnMut(OperationEvent),
+{
+    let Some(exe) = running_client(addons_dir) else {
+        return Ok(());
+    };
+    let message = format!(
+        "The game is running from {}, close it before {action}",
+        exe.display()
+    );
+    reporter(OperationEvent::Warning(message.clone()));
+    Err(AddonError::new(AddonErrorKind::GameRunning, message).with_path(addons_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_addons_dir;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_wine_process_runs_from_game_dir() {
+        let (temp, addons_dir) = setup_addons_dir();
+        let game = game_dir(&addons_dir).unwrap();
+        fs::write(game.join("Wow.exe"), "").unwrap();
+
+        // A prefix whose d: drive points at the parent of the game directory
+        let prefix = temp.path().join("prefix");
+        fs::create_dir_all(prefix.join("dosdevices")).unwrap();
+        std::os::unix::fs::symlink(game.parent().unwrap(), prefix.join("dosdevices/d:")).unwrap();
+        let game_name = game.file_name().unwrap().to_string_lossy();
+
+        let wine = Process {
+            exe: PathBuf::from("/usr/bin/wine64-preloader"),
+            cmdline: vec![format!("D:\\{game_name}\\WOW.EXE"), "-console".to_string()],
+            wine_prefix: Some(prefix.clone()),
+        };
+        let exes = wine.executables();
+        println!("{exes:?}");
+        assert_eq!(exes.len(), 1);
+        assert!(runs_from(&exes[0], &game));
+
+        let native = Process {
+            exe: game.join("wow"),
+            ..Default::default()
+        };
+        assert!(runs_from(&native.executables()[0], &game));
+
+        let other = Process {
+            exe: PathBuf::from("/usr/bin/wine64-preloader"),
+            cmdline: vec!["C:\\windows\\system32\\explorer.exe".to_string()],
+            wine_prefix: Some(prefix),
+        };
+        assert!(!other.executables().iter().any(|exe| runs_from(exe, &game)));
+    }
+}
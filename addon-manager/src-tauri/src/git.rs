@@ -1,7 +1,12 @@
-use git2::{FetchOptions, RemoteCallbacks, Repository};
+use git2::{BranchType, FetchOptions, RemoteCallbacks, Repository, build::CheckoutBuilder};
 use std::path::PathBuf;
 use url::Url;
 
+use crate::{
+    error::{AddonError, AddonErrorKind},
+    retry::{self, RetryAttempt, RetryPolicy},
+};
+
 /// Extracts the owner and repository name from a Git URL.
 // This assumes the URL is in the format: https://github.com/owner/repo.git
 /// ```
@@ -26,6 +31,9 @@ pub fn extract_owner_repo_from_url(url: &str) -> Result<(String, String), String
 }
 
 /// Clones a git repository into the given base path.
+/// Return `false` from `progress` to abort the transfer.
+/// Transient network failures are retried; `on_retry` is called before each retry
+/// and can return `false` to give up.
 ///
 /// # Example
 ///
@@ -36,37 +44,75 @@ pub fn extract_owner_repo_from_url(url: &str) -> Result<(String, String), String
 /// let temp = tempdir().unwrap();
 /// let base_path = temp.path().to_path_buf();
 /// let url = "https://github.com/sogladev/addon-335-train-all-button.git";
-/// let repo = clone_git_repo(url, base_path.clone(), &mut |progress, total| { println!("progress: {progress}/{total}"); }).unwrap();
+/// let repo = clone_git_repo(
+///     url,
+///     base_path.clone(),
+///     &mut |progress, total| { println!("progress: {progress}/{total}"); true },
+///     &mut |retry| { println!("{}", retry.message("Clone")); true },
+/// )
+/// .unwrap();
 /// let repo_dir = base_path.join("addon-335-train-all-button");
 /// assert!(repo_dir.exists());
 /// assert!(repo_dir.join(".git").is_dir());
 /// assert!(repo_dir.join("TrainerButton").is_dir());
 /// ```
-pub fn clone_git_repo<F>(
+pub fn clone_git_repo<F, R>(
     url: &str,
     base_path: PathBuf,
     progress: &mut F,
-) -> Result<Repository, String>
+    on_retry: &mut R,
+) -> Result<Repository, AddonError>
 where
-    F: FnMut(usize, usize) + Send,
+    F: FnMut(usize, usize) -> bool,
+    R: FnMut(&RetryAttempt) -> bool,
 {
-    let (_owner, repo) = extract_owner_repo_from_url(url)?;
+    let (_owner, repo) = extract_owner_repo_from_url(url).map_err(|e| {
+        AddonError::new(AddonErrorKind::InvalidInput, "Invalid repo URL")
+            .with_url(url)
+            .with_cause(e)
+    })?;
 
     let target_path = base_path.join(&repo);
 
-    let mut callbacks = RemoteCallbacks::new();
-    callbacks.transfer_progress(move |stats| {
-        progress(stats.received_objects(), stats.total_objects());
-        true
-    });
+    let existed = target_path.exists();
+    let clone_once = || {
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks
+            .transfer_progress(|stats| progress(stats.received_objects(), stats.total_objects()));
+
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(callbacks);
 
-    let mut fetch_options = FetchOptions::new();
-    fetch_options.remote_callbacks(callbacks);
+        let mut builder = git2::build::RepoBuilder::new();
+        builder.fetch_options(fetch_options);
 
-    let mut builder = git2::build::RepoBuilder::new();
-    builder.fetch_options(fetch_options);
+        let result = builder.clone(url, &target_path);
+        if result.is_err() && !existed && target_path.exists() {
+            // A failed clone can leave a partial checkout that blocks the next attempt
+            std::fs::remove_dir_all(&target_path).ok();
+        }
+        result
+    };
+
+    retry::retry_transient(&RetryPolicy::default(), clone_once, on_retry).map_err(|e| {
+        AddonError::git("Failed to clone repository", &e)
+            .with_url(url)
+            .with_path(&target_path)
+    })
+}
 
-    builder
-        .clone(url, &target_path)
-        .map_err(|e| e.message().to_string())
+/// Check out `origin/<branch>` of a fresh clone as a local branch tracking it.
+/// Returns false when the remote has no such branch.
+pub fn checkout_remote_branch(repo: &Repository, branch: &str) -> Result<bool, git2::Error> {
+    let Ok(remote) = repo.find_reference(&format!("refs/remotes/origin/{branch}")) else {
+        return Ok(false);
+    };
+    let commit = remote.peel_to_commit()?;
+    if repo.find_branch(branch, BranchType::Local).is_err() {
+        let mut local = repo.branch(branch, &commit, false)?;
+        local.set_upstream(Some(&format!("origin/{branch}")))?;
+    }
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))?;
+    repo.set_head(&format!("refs/heads/{branch}"))?;
+    Ok(true)
 }
@@ -1,18 +1,41 @@
-use std::path::{Path, PathBuf};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+};
 
 use tauri::{AppHandle, Emitter};
 
-use crate::{addon_disk, git, operation_reporter::*, validate};
+use crate::{
+    addon_disk, addon_store,
+    deploy::{self, LinkOptions},
+    error::{AddonError, AddonErrorKind},
+    game_process, git,
+    lock::OperationLock,
+    operation_history::finish_operation,
+    operation_registry::{CancellationToken, OperationRegistry},
+    operation_reporter::*,
+    validate,
+};
 
 pub struct InstallReporter {
     pub event: Box<dyn FnMut(OperationEvent) + Send>,
 }
 
-pub fn install_addon<F>(url: String, dir: String, mut reporter: F) -> Result<(), String>
+/// Clone a repository into `.addonmanager` and link its sub-addons.
+/// Returns the names of the linked sub-addons.
+pub fn install_addon<F>(
+    url: String,
+    dir: String,
+    link: LinkOptions,
+    preferred_branch: Option<&str>,
+    cancel: &CancellationToken,
+    mut reporter: F,
+) -> Result<Vec<String>, AddonError>
 where
     F: FnMut(OperationEvent) + Send,
 {
     let dir = Path::new(&dir);
+    game_process::ensure_not_running(dir, "installing addons", &mut reporter)?;
 
     reporter(OperationEvent::Status(
         "Starting addon installation...".to_string(),
@@ -21,8 +44,12 @@ where
     let manager_dir = validate::ensure_manager_dir(dir)?;
 
     reporter(OperationEvent::Status("Cloning repository...".to_string()));
-    let (_owner, repo_name) =
-        git::extract_owner_repo_from_url(&url).map_err(|e| format!("Invalid repo URL: {e}"))?;
+    let (_owner, repo_name) = git::extract_owner_repo_from_url(&url).map_err(|e| {
+        AddonError::new(AddonErrorKind::InvalidInput, "Invalid repo URL")
+            .with_url(&url)
+            .with_cause(e)
+    })?;
+    let _lock = OperationLock::acquire_repo(dir, &repo_name, "install")?;
     let repo_path = manager_dir.join(repo_name);
 
     if repo_path.exists() {
@@ -31,44 +58,86 @@ where
 
     // Throttle progress events: only emit on 1% increments
     let mut last_percent: u32 = 0;
-    let repo = git::clone_git_repo(&url, manager_dir.clone(), &mut |current, total| {
-        if total > 0 {
-            let percent = ((current as u128 * 100) / total as u128) as u32;
-            if percent != last_percent {
-                last_percent = percent;
-                reporter(OperationEvent::Progress { current, total });
+    // Shared by the progress and retry callbacks, which are both borrowed during the clone
+    let clone_reporter = RefCell::new(&mut reporter);
+    let clone_result = git::clone_git_repo(
+        &url,
+        manager_dir.clone(),
+        &mut |current, total| {
+            if cancel.is_cancelled() {
+                return false;
+            }
+            if total > 0 {
+                let percent = ((current as u128 * 100) / total as u128) as u32;
+                if percent != last_percent {
+                    last_percent = percent;
+                    (clone_reporter.borrow_mut())(OperationEvent::Progress { current, total });
+                }
             }
+            true
+        },
+        &mut |retry| {
+            (clone_reporter.borrow_mut())(OperationEvent::Warning(retry.message("Clone")));
+            !cancel.is_cancelled()
+        },
+    );
+    let repo = match clone_result {
+        Ok(repo) => repo,
+        Err(_) if cancel.is_cancelled() => {
+            // Remove the partial clone so the folder is not left half-installed
+            if repo_path.exists() {
+                std::fs::remove_dir_all(&repo_path).ok();
+            }
+            return Err(
+                AddonError::new(AddonErrorKind::Cancelled, "Installation cancelled").with_url(url),
+            );
         }
-    })
-    .map_err(|e| format!("Failed to clone repository from {url}: {e}"))?;
+        Err(e) => return Err(e),
+    };
 
     let path = PathBuf::from(
         repo.workdir()
             .expect("Repository has no workdir. It should not be bare"),
     );
 
+    if let Some(branch) = preferred_branch {
+        match git::checkout_remote_branch(&repo, branch) {
+            Ok(true) => reporter(OperationEvent::Status(format!(
+                "Checked out preferred branch {branch}"
+            ))),
+            Ok(false) => reporter(OperationEvent::Warning(format!(
+                "Branch {branch} does not exist, staying on the default branch"
+            ))),
+            Err(e) => reporter(OperationEvent::Warning(format!(
+                "Failed to check out preferred branch {branch}: {e}"
+            ))),
+        }
+    }
+
     reporter(OperationEvent::Status(
         "Discovering sub-addons...".to_string(),
     ));
-    let disk_repo = addon_disk::create_disk_addon_repository(&path)
-        .map_err(|e| format!("Failed to discover sub-addons: {e}"))?;
+    let disk_repo = addon_disk::create_disk_addon_repository(&path)?;
 
     reporter(OperationEvent::Status(
         "Installing sub-addons (symlinking)...".to_string(),
     ));
-    install_sub_addons(disk_repo.addons, &path, dir, &mut reporter);
+    let linked = install_sub_addons(disk_repo.addons, &path, dir, link, &mut reporter)?;
 
     reporter(OperationEvent::Status(
         "Addon installation complete.".to_string(),
     ));
-    Ok(())
+    Ok(linked)
 }
 
+/// Copy a local folder into `.addonmanager` and link its sub-addons.
+/// Returns the names of the linked sub-addons.
 pub fn install_local_folder<F>(
     source_path: String,
     dir: String,
+    link: LinkOptions,
     mut reporter: F,
-) -> Result<(), String>
+) -> Result<Vec<String>, AddonError>
 where
     F: FnMut(OperationEvent) + Send,
 {
@@ -77,18 +146,20 @@ where
 
     // Validate source folder exists and contains .toc files
     if !source.exists() {
-        return Err(format!(
-            "Source folder does not exist: {}",
-            source.display()
-        ));
+        return Err(
+            AddonError::new(AddonErrorKind::NotFound, "Source folder does not exist")
+                .with_path(source),
+        );
     }
     if !source.is_dir() {
-        return Err(format!(
-            "Source path is not a directory: {}",
-            source.display()
-        ));
+        return Err(AddonError::new(
+            AddonErrorKind::InvalidInput,
+            "Source path is not a directory",
+        )
+        .with_path(source));
     }
 
+    game_process::ensure_not_running(dir, "installing addons", &mut reporter)?;
     reporter(OperationEvent::Status(
         "Starting local folder installation...".to_string(),
     ));
@@ -98,7 +169,9 @@ where
     // Get folder name from source path
     let folder_name = source
         .file_name()
-        .ok_or("Invalid source path")?
+        .ok_or_else(|| {
+            AddonError::new(AddonErrorKind::InvalidInput, "Invalid source path").with_path(source)
+        })?
         .to_string_lossy()
         .to_string();
 
@@ -107,32 +180,34 @@ where
         folder_name
     )));
 
+    let _lock = OperationLock::acquire_repo(dir, &folder_name, "install")?;
     let dest_path = manager_dir.join(&folder_name);
 
     // Remove existing folder if it exists
     if dest_path.exists() {
-        std::fs::remove_dir_all(&dest_path)
-            .map_err(|e| format!("Failed to remove existing folder: {e}"))?;
+        std::fs::remove_dir_all(&dest_path).map_err(|e| {
+            AddonError::io("Failed to remove existing folder", &e).with_path(&dest_path)
+        })?;
     }
 
     // Copy the entire folder to .addonmanager
-    copy_dir_recursive(source, &dest_path).map_err(|e| format!("Failed to copy folder: {e}"))?;
+    copy_dir_recursive(source, &dest_path)
+        .map_err(|e| AddonError::io("Failed to copy folder", &e).with_path(&dest_path))?;
 
     reporter(OperationEvent::Status(
         "Discovering sub-addons...".to_string(),
     ));
-    let disk_repo = addon_disk::create_non_git_addon_repository(&dest_path)
-        .map_err(|e| format!("Failed to discover sub-addons: {e}"))?;
+    let disk_repo = addon_disk::create_non_git_addon_repository(&dest_path)?;
 
     reporter(OperationEvent::Status(
         "Installing sub-addons (symlinking)...".to_string(),
     ));
-    install_sub_addons(disk_repo.addons, &dest_path, dir, &mut reporter);
+    let linked = install_sub_addons(disk_repo.addons, &dest_path, dir, link, &mut reporter)?;
 
     reporter(OperationEvent::Status(
         "Local folder installation complete.".to_string(),
     ));
-    Ok(())
+    Ok(linked)
 }
 
 fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
@@ -151,14 +226,22 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Link every sub-addon of a repository and return the names of the linked ones.
+/// Sub-addons that fail to link are reported as errors. Callers make sure the
+/// game is not running.
 pub fn install_sub_addons<F>(
     addons: Vec<addon_disk::DiskAddon>,
     repo_root: &Path,
     addons_dir: &Path,
+    link: LinkOptions,
     mut reporter: F,
-) where
+) -> Result<Vec<String>, AddonError>
+where
     F: FnMut(OperationEvent) + Send,
 {
+    let _folder_lock = OperationLock::acquire_folder(addons_dir, "link")?;
+
+    let mut linked = Vec::new();
     for addon in addons {
         let symlink_name = &addon.name;
         let target_dir = if addon.dir == "." {
@@ -168,15 +251,20 @@ pub fn install_sub_addons<F>(
         };
         let symlink_path = addons_dir.join(symlink_name);
 
-        if symlink_path.exists() {
+        if symlink_path.exists() || symlink_path.is_symlink() {
             let msg = format!(
                 "Removing existing symlink or directory: {}",
                 symlink_path.display()
             );
             reporter(OperationEvent::Status(msg.clone()));
-            std::fs::remove_file(&symlink_path)
-                .or_else(|_| std::fs::remove_dir_all(&symlink_path))
-                .ok();
+            if !matches!(
+                deploy::remove_deployment(addons_dir, symlink_name),
+                Ok(true)
+            ) {
+                std::fs::remove_file(&symlink_path)
+                    .or_else(|_| std::fs::remove_dir_all(&symlink_path))
+                    .ok();
+            }
         }
 
         if addon.names.len() > 1 {
@@ -187,18 +275,51 @@ pub fn install_sub_addons<F>(
         }
 
         reporter(OperationEvent::Status(format!(
-            "Creating symlink for '{symlink_name}': {} -> {}",
+            "Creating {} for '{symlink_name}': {} -> {}",
+            link.strategy.noun(),
             target_dir.display(),
             symlink_path.display()
         )));
-        if let Err(e) = crate::symlink::create_symlink(&target_dir, &symlink_path) {
-            reporter(OperationEvent::Error(format!(
-                "Failed to create symlink for '{symlink_name}': {} -> {} ({e})",
-                target_dir.display(),
-                symlink_path.display(),
-            )));
+        match deploy::link_addon(addons_dir, &target_dir, &symlink_path, link) {
+            Ok(()) => linked.push(symlink_name.clone()),
+            Err(e) => reporter(OperationEvent::Error(AddonError {
+                message: format!(
+                    "Failed to create {} for '{symlink_name}' -> {}",
+                    link.strategy.noun(),
+                    target_dir.display()
+                ),
+                ..e
+            })),
         }
     }
+    Ok(linked)
+}
+
+/// Link a single sub-addon of an installed repository, replacing whatever is in its place
+pub fn link_sub_addon(
+    addon: &addon_disk::DiskAddon,
+    repo_root: &Path,
+    addons_dir: &Path,
+    link: LinkOptions,
+) -> Result<(), AddonError> {
+    game_process::ensure_not_running(addons_dir, "linking addons", &mut |_| {})?;
+    let _folder_lock = OperationLock::acquire_folder(addons_dir, "link")?;
+    let symlink_name = &addon.name;
+    let target_dir = if addon.dir == "." {
+        repo_root.to_path_buf()
+    } else {
+        repo_root.join(&addon.dir)
+    };
+    let symlink_path = addons_dir.join(symlink_name);
+    // Remove any existing symlink, deployed copy or directory
+    if (symlink_path.exists() || symlink_path.is_symlink())
+        && !deploy::remove_deployment(addons_dir, symlink_name)?
+    {
+        std::fs::remove_file(&symlink_path)
+            .or_else(|_| std::fs::remove_dir_all(&symlink_path))
+            .ok();
+    }
+    deploy::link_addon(addons_dir, &target_dir, &symlink_path, link)
 }
 
 #[tauri::command]
@@ -206,6 +327,7 @@ pub async fn install_addon_cmd(
     app_handle: tauri::AppHandle,
     url: String,
     path: String,
+    registry: tauri::State<'_, OperationRegistry>,
 ) -> Result<(), String> {
     // Create operation key for tracking
     let operation_key = OperationKey {
@@ -217,22 +339,27 @@ pub async fn install_addon_cmd(
     let operation_key_clone = operation_key.clone();
 
     // Emit started event
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key.clone(),
-                event: OperationEvent::Started {
-                    operation: OperationType::Install,
-                },
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+    emit_operation_event(
+        &app_handle,
+        &operation_key,
+        OperationEvent::Started {
+            operation: OperationType::Install,
+        },
+    )
+    .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
 
     let mut first_progress_emitted = false;
 
+    let cancel = registry.register(&operation_key);
+    let task_cancel = cancel.clone();
+    let link = deploy::configured_link_options(&app_handle, &path);
+    let preferred_branch = addon_store::load_user_config(&app_handle)?
+        .folder_settings(&path)
+        .preferred_branch;
+
     let install_result = tauri::async_runtime::spawn_blocking(move || {
-        install_addon(url, path, |event| {
+        let branch = preferred_branch.as_deref();
+        install_addon(url, path, link, branch, &task_cancel, |event| {
             if let OperationEvent::Progress { .. } = event
                 && !first_progress_emitted
             {
@@ -241,48 +368,65 @@ pub async fn install_addon_cmd(
                 });
                 first_progress_emitted = true;
             }
-            if let Err(e) = app_handle.emit(
-                "operation-event",
-                OperationEventPayload {
-                    key: operation_key.clone(),
-                    event,
-                },
-            ) {
+            if let Err(e) = emit_operation_event(&app_handle, &operation_key, event) {
                 eprintln!("Failed to emit operation-event: {e}");
             }
         })
     })
-    .await
-    .map_err(|e| format!("Task join error: {e}"))?;
+    .await;
+    registry.unregister(&operation_key_clone, &cancel);
+    let install_result = install_result.map_err(|e| format!("Task join error: {e}"))?;
 
     match install_result {
-        Ok(_) => {
-            app_handle_clone
-                .emit(
-                    "operation-event",
-                    OperationEventPayload {
-                        key: operation_key_clone.clone(),
-                        event: OperationEvent::Completed,
-                    },
-                )
-                .map_err(|e| format!("Failed to emit operation-completed: {e}"))?;
+        Err(_) if cancel.is_cancelled() => {
+            if let Err(e) = emit_operation_event(
+                &app_handle_clone,
+                &operation_key_clone,
+                OperationEvent::Cancelled,
+            ) {
+                eprintln!("Failed to emit operation-event: {e}");
+            }
+            finish_operation(&app_handle_clone, &operation_key_clone);
             app_handle_clone
                 .emit("addon-data-updated", ())
                 .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))?;
             Ok(())
         }
-        Err(err_msg) => {
+        Ok(linked) => {
+            save_linked(&app_handle_clone, &operation_key_clone, &linked);
+            if let Err(e) = emit_operation_event(
+                &app_handle_clone,
+                &operation_key_clone,
+                OperationEvent::Completed,
+            ) {
+                eprintln!("Failed to emit operation-event: {e}");
+            }
+            finish_operation(&app_handle_clone, &operation_key_clone);
             app_handle_clone
-                .emit(
-                    "operation-event",
-                    OperationEventPayload {
-                        key: operation_key_clone.clone(),
-                        event: OperationEvent::Error(err_msg.clone()),
-                    },
-                )
-                .map_err(|e| format!("Failed to emit operation-error: {e}"))?;
-            Err(err_msg)
+                .emit("addon-data-updated", ())
+                .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))?;
+            Ok(())
         }
+        Err(err) => {
+            if let Err(e) = emit_operation_event(
+                &app_handle_clone,
+                &operation_key_clone,
+                OperationEvent::Error(err.clone()),
+            ) {
+                eprintln!("Failed to emit operation-event: {e}");
+            }
+            finish_operation(&app_handle_clone, &operation_key_clone);
+            Err(err.into())
+        }
+    }
+}
+
+/// Save the sub-addons linked by an install as enabled, so Repair restores them
+pub fn save_linked(store: &impl addon_store::ConfigStore, key: &OperationKey, linked: &[String]) {
+    if let Err(e) =
+        addon_store::set_addons_enabled(store, &key.folder_path, &key.repo_url, linked, true)
+    {
+        eprintln!("Failed to save enabled state for {}: {e}", key.repo_url);
     }
 }
 
@@ -307,27 +451,19 @@ pub async fn install_local_folder_cmd(
     let operation_key_clone = operation_key.clone();
 
     // Emit started event
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key.clone(),
-                event: OperationEvent::Started {
-                    operation: OperationType::Install,
-                },
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
-
+    emit_operation_event(
+        &app_handle,
+        &operation_key,
+        OperationEvent::Started {
+            operation: OperationType::Install,
+        },
+    )
+    .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+
+    let link = deploy::configured_link_options(&app_handle, &path);
     let install_result = tauri::async_runtime::spawn_blocking(move || {
-        install_local_folder(source_path, path, |event| {
-            if let Err(e) = app_handle.emit(
-                "operation-event",
-                OperationEventPayload {
-                    key: operation_key.clone(),
-                    event,
-                },
-            ) {
+        install_local_folder(source_path, path, link, |event| {
+            if let Err(e) = emit_operation_event(&app_handle, &operation_key, event) {
                 eprintln!("Failed to emit operation-event: {e}");
             }
         })
@@ -336,32 +472,31 @@ pub async fn install_local_folder_cmd(
     .map_err(|e| format!("Task join error: {e}"))?;
 
     match install_result {
-        Ok(_) => {
-            app_handle_clone
-                .emit(
-                    "operation-event",
-                    OperationEventPayload {
-                        key: operation_key_clone.clone(),
-                        event: OperationEvent::Completed,
-                    },
-                )
-                .map_err(|e| format!("Failed to emit operation-completed: {e}"))?;
+        Ok(linked) => {
+            save_linked(&app_handle_clone, &operation_key_clone, &linked);
+            if let Err(e) = emit_operation_event(
+                &app_handle_clone,
+                &operation_key_clone,
+                OperationEvent::Completed,
+            ) {
+                eprintln!("Failed to emit operation-event: {e}");
+            }
+            finish_operation(&app_handle_clone, &operation_key_clone);
             app_handle_clone
                 .emit("addon-data-updated", ())
                 .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))?;
             Ok(())
         }
-        Err(err_msg) => {
-            app_handle_clone
-                .emit(
-                    "operation-event",
-                    OperationEventPayload {
-                        key: operation_key_clone.clone(),
-                        event: OperationEvent::Error(err_msg.clone()),
-                    },
-                )
-                .map_err(|e| format!("Failed to emit operation-error: {e}"))?;
-            Err(err_msg)
+        Err(err) => {
+            if let Err(e) = emit_operation_event(
+                &app_handle_clone,
+                &operation_key_clone,
+                OperationEvent::Error(err.clone()),
+            ) {
+                eprintln!("Failed to emit operation-event: {e}");
+            }
+            finish_operation(&app_handle_clone, &operation_key_clone);
+            Err(err.into())
         }
     }
 }
@@ -379,81 +514,78 @@ pub async fn create_addon_symlink(
         folder_path: folder_path.clone(),
     };
 
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key.clone(),
-                event: OperationEvent::Started {
-                    operation: OperationType::Install,
-                },
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
-
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key.clone(),
-                event: OperationEvent::Status(format!("Creating symlink for '{addon_name}'")),
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
-
-    let result: Result<(), String> = async {
+    emit_operation_event(
+        &app_handle,
+        &operation_key,
+        OperationEvent::Started {
+            operation: OperationType::Install,
+        },
+    )
+    .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+
+    emit_operation_event(
+        &app_handle,
+        &operation_key,
+        OperationEvent::Status(format!("Creating symlink for '{addon_name}'")),
+    )
+    .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+
+    let result: Result<(), AddonError> = async {
         // Find repo directory
-        let disk_state = state
-            .get_disk_state()
-            .map_err(|e| format!("Disk state error: {e}"))?;
-        let folder = disk_state.get(&folder_path).ok_or("Folder not found")?;
+        let disk_state = state.get_disk_state().map_err(|e| {
+            AddonError::new(AddonErrorKind::Other, "Disk state error").with_cause(e)
+        })?;
+        let folder = disk_state.get(&folder_path).ok_or_else(|| {
+            AddonError::new(AddonErrorKind::NotFound, "Folder not found").with_path(&folder_path)
+        })?;
         let repo = folder
             .repositories
             .iter()
             .find(|r| r.get_key() == repo_url)
-            .ok_or("Repo not found")?;
+            .ok_or_else(|| {
+                AddonError::new(AddonErrorKind::NotFound, "Repo not found").with_url(&repo_url)
+            })?;
         let addon = repo
             .addons
             .iter()
             .find(|a| a.name == addon_name)
-            .ok_or("Addon not found")?;
+            .ok_or_else(|| {
+                AddonError::new(
+                    AddonErrorKind::NotFound,
+                    format!("Addon not found: {addon_name}"),
+                )
+            })?;
         let repo_root = Path::new(&folder_path)
             .join(".addonmanager")
             .join(repo.get_name());
-        let addons_dir = Path::new(&folder_path);
-        let symlink_name = &addon.name;
-        let target_dir = if addon.dir == "." {
-            repo_root.to_path_buf()
-        } else {
-            repo_root.join(&addon.dir)
-        };
-        let symlink_path = addons_dir.join(symlink_name);
-        // Remove any existing symlink or directory
-        if symlink_path.exists() {
-            std::fs::remove_file(&symlink_path)
-                .or_else(|_| std::fs::remove_dir_all(&symlink_path))
-                .ok();
-        }
-        crate::symlink::create_symlink(&target_dir, &symlink_path)
-            .map_err(|e| format!("Failed to create symlink: {e}"))
+        let link = deploy::configured_link_options(&app_handle, &folder_path);
+        link_sub_addon(addon, &repo_root, Path::new(&folder_path), link)
     }
     .await;
 
+    if result.is_ok()
+        && let Err(e) = crate::addon_store::set_addon_enabled(
+            &app_handle,
+            &folder_path,
+            &repo_url,
+            &addon_name,
+            true,
+        )
+    {
+        eprintln!("Failed to save enabled state for '{addon_name}': {e}");
+    }
+
     let completion_event = match &result {
         Ok(_) => OperationEvent::Completed,
         Err(e) => OperationEvent::Error(e.clone()),
     };
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key,
-                event: completion_event,
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+    if let Err(e) = emit_operation_event(&app_handle, &operation_key, completion_event) {
+        eprintln!("Failed to emit operation-event: {e}");
+    }
 
-    result
+    finish_operation(&app_handle, &operation_key);
+
+    result.map_err(Into::into)
 }
 
 #[tauri::command]
@@ -468,55 +600,47 @@ pub async fn remove_addon_symlink(
         folder_path: folder_path.clone(),
     };
 
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key.clone(),
-                event: OperationEvent::Started {
-                    operation: OperationType::Delete,
-                },
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
-
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key.clone(),
-                event: OperationEvent::Status(format!("Removing symlink for '{addon_name}'")),
-            },
+    emit_operation_event(
+        &app_handle,
+        &operation_key,
+        OperationEvent::Started {
+            operation: OperationType::Delete,
+        },
+    )
+    .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+
+    emit_operation_event(
+        &app_handle,
+        &operation_key,
+        OperationEvent::Status(format!("Removing symlink for '{addon_name}'")),
+    )
+    .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+
+    let result = deploy::unlink_addon(Path::new(&folder_path), &addon_name);
+
+    if result.is_ok()
+        && let Err(e) = crate::addon_store::set_addon_enabled(
+            &app_handle,
+            &folder_path,
+            &repo_url,
+            &addon_name,
+            false,
         )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
-
-    let result: Result<(), String> = async {
-        let addons_dir = Path::new(&folder_path);
-        let symlink_path = addons_dir.join(&addon_name);
-        if symlink_path.exists() {
-            std::fs::remove_file(&symlink_path)
-                .or_else(|_| std::fs::remove_dir_all(&symlink_path))
-                .map_err(|e| format!("Failed to remove symlink: {e}"))?;
-        }
-        Ok(())
+    {
+        eprintln!("Failed to save enabled state for '{addon_name}': {e}");
     }
-    .await;
 
     let completion_event = match &result {
         Ok(_) => OperationEvent::Completed,
         Err(e) => OperationEvent::Error(e.clone()),
     };
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key,
-                event: completion_event,
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+    if let Err(e) = emit_operation_event(&app_handle, &operation_key, completion_event) {
+        eprintln!("Failed to emit operation-event: {e}");
+    }
+
+    finish_operation(&app_handle, &operation_key);
 
-    result
+    result.map_err(Into::into)
 }
 
 #[cfg(test)]
@@ -539,9 +663,16 @@ mod tests {
         print_dir_tree(addons_dir_str);
         assert!(result.is_ok(), "ensure_manager_dir failed: {:?}", result);
 
-        let result = install_addon(url.clone(), addons_dir_str.to_string(), move |event| {
-            println!("Install event: {:?}", event);
-        });
+        let result = install_addon(
+            url.clone(),
+            addons_dir_str.to_string(),
+            LinkOptions::default(),
+            None,
+            &CancellationToken::default(),
+            move |event| {
+                println!("Install event: {:?}", event);
+            },
+        );
         println!("Directory tree under AddOns after install_addon:");
         print_dir_tree(addons_dir_str);
         assert!(result.is_ok(), "install_addon failed: {:?}", result);
@@ -609,7 +740,14 @@ mod tests {
         println!("Before install_sub_addons:");
         print_dir_tree(addons_dir.to_str().unwrap());
 
-        install_sub_addons(vec![sub_addon], &repo_root, &addons_dir, |_| {});
+        install_sub_addons(
+            vec![sub_addon],
+            &repo_root,
+            &addons_dir,
+            LinkOptions::default(),
+            |_| {},
+        )
+        .unwrap();
 
         println!("After install_sub_addons:");
         print_dir_tree(addons_dir.to_str().unwrap());
@@ -659,6 +797,7 @@ mod tests {
         let result = install_local_folder(
             source_addon_dir.to_str().unwrap().to_string(),
             addons_path.clone(),
+            LinkOptions::default(),
             |_| {},
         );
         assert!(result.is_ok(), "install_local_folder failed: {:?}", result);
@@ -711,8 +850,10 @@ mod tests {
             disk_repo.addons.clone(),
             &local_folder_path,
             &addons_dir,
+            LinkOptions::default(),
             |_| {},
-        );
+        )
+        .unwrap();
 
         println!("After repair (install_sub_addons):");
         print_dir_tree(addons_dir.to_str().unwrap());
@@ -733,7 +874,14 @@ mod tests {
         print_dir_tree(addons_dir.to_str().unwrap());
 
         // Repair again
-        install_sub_addons(disk_repo.addons, &local_folder_path, &addons_dir, |_| {});
+        install_sub_addons(
+            disk_repo.addons,
+            &local_folder_path,
+            &addons_dir,
+            LinkOptions::default(),
+            |_| {},
+        )
+        .unwrap();
 
         println!("After second repair:");
         print_dir_tree(addons_dir.to_str().unwrap());
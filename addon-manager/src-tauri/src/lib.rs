@@ -1,18 +1,40 @@
 pub mod addon_discovery;
 pub mod addon_disk;
 pub mod addon_store;
+pub mod backup;
+pub mod character_addons;
+pub mod cli;
+pub mod deploy;
+pub mod doctor;
+pub mod error;
+pub mod game_discovery;
+pub mod game_process;
 pub mod git;
 pub mod install;
+pub mod lock;
+pub mod operation_history;
+pub mod operation_registry;
 pub mod operation_reporter;
+pub mod orphans;
 pub mod permission_workaround;
+pub mod plan;
 pub mod remove;
+pub mod repair;
+pub mod retry;
+pub mod saved_variables;
+pub mod scheduler;
 pub mod symlink;
 #[cfg(test)]
 pub mod test_utils;
+pub mod trash;
 pub mod update;
 pub mod validate;
 pub mod view_models;
 
+use std::path::PathBuf;
+
+use tauri::Manager;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -20,10 +42,16 @@ pub fn run() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_fs::init())
         .manage(addon_discovery::AppState::default())
+        .manage(operation_registry::OperationRegistry::default())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_store::Builder::default().build())
-        .setup(|_app| Ok(()))
+        .setup(|app| {
+            let log_dir = app.path().app_data_dir()?;
+            app.manage(operation_history::OperationHistory::new(log_dir));
+            scheduler::start(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             addon_discovery::refresh_addon_data,
             addon_discovery::refresh_disk_data,
@@ -32,12 +60,38 @@ pub fn run() {
             addon_store::delete_addon_directory,
             addon_store::load_theme,
             addon_store::save_theme,
+            addon_store::save_trash_retention,
+            addon_store::save_backup_count,
+            addon_store::save_bulk_update_excluded,
+            addon_store::save_link_style,
+            addon_store::save_repo_auto_update,
+            addon_store::save_update_check_interval,
+            addon_store::update_folder_settings,
+            backup::list_backups_cmd,
+            backup::restore_backup_cmd,
+            character_addons::list_characters_cmd,
+            character_addons::list_character_addons_cmd,
+            character_addons::set_character_addon_enabled_cmd,
+            doctor::doctor_cmd,
+            game_discovery::discover_game_installs_cmd,
             install::create_addon_symlink,
             install::install_addon_cmd,
             install::install_local_folder_cmd,
             install::remove_addon_symlink,
+            operation_history::get_operation_history,
+            operation_registry::cancel_operation_cmd,
+            orphans::cleanup_orphans_cmd,
+            orphans::scan_orphans_cmd,
             permission_workaround::allow_file,
+            plan::plan_delete_cmd,
+            plan::plan_install_cmd,
+            plan::plan_update_all_cmd,
+            plan::plan_update_cmd,
             remove::delete_addon_cmd,
+            remove::find_saved_variables_cmd,
+            repair::repair_folder_cmd,
+            trash::list_deleted_addons_cmd,
+            trash::restore_deleted_addon_cmd,
             update::update_addon_cmd,
             update::update_all_addons_cmd,
             validate::is_valid_addons_folder_str,
@@ -47,52 +101,48 @@ pub fn run() {
         .expect("error while running tauri application");
 }
 
-pub fn run_headless(quiet: bool) {
-    let app = tauri::Builder::default()
-        .plugin(tauri_plugin_store::Builder::default().build())
-        .plugin(tauri_plugin_fs::init())
-        .manage(addon_discovery::AppState::default())
-        .setup(|_app| Ok(()))
-        .build(tauri::generate_context!())
-        .expect("failed to build minimal Tauri app");
-
-    let handle = app.handle();
-    let state = tauri::Manager::state::<addon_discovery::AppState>(&app).clone();
-
-    tauri::async_runtime::block_on(async {
-        match addon_discovery::check_for_updates(handle.clone(), state.clone()).await {
-            Ok(folders) => {
-                if !quiet {
-                    println!("Scanned {} AddOns folders", folders.len());
-                    for folder in &folders {
-                        if !folder.repositories.is_empty() {
-                            println!(
-                                "  - {}: {} repositories",
-                                folder.path,
-                                folder.repositories.len()
-                            );
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to check for updates: {e}");
-                std::process::exit(1);
-            }
-        }
+/// Config of the command line: the given file or the store file of the app
+fn cli_config_store(config: Option<PathBuf>) -> addon_store::FileConfigStore {
+    config
+        .map(addon_store::FileConfigStore::new)
+        .or_else(addon_store::FileConfigStore::default_location)
+        .unwrap_or_else(|| {
+            eprintln!("Error: Could not determine the app data directory, pass --config");
+            std::process::exit(cli::EXIT_USAGE)
+        })
+}
 
-        match update::update_all_addons_cmd(handle.clone(), state).await {
-            Ok(_) => {
-                if !quiet {
-                    println!("All addons are up-to-date");
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to update addons: {e}");
-                std::process::exit(1);
-            }
+/// Check for updates and update all outdated addons matching the filter, without
+/// starting the app. With `check` only the outdated addons are listed.
+pub fn run_headless(
+    config: Option<PathBuf>,
+    quiet: bool,
+    dry_run: bool,
+    check: bool,
+    filter: update::UpdateFilter,
+    format: cli::OutputFormat,
+) {
+    let command = if check {
+        cli::Command::Check { filter }
+    } else {
+        cli::Command::Update {
+            repo: None,
+            filter,
+            dry_run,
         }
-    });
+    };
+    run_cli(config, command, quiet, format)
+}
 
-    std::process::exit(0)
+/// Run a single CLI subcommand and exit with its status code.
+/// Operations are recorded in the same history as the app's.
+pub fn run_cli(
+    config: Option<PathBuf>,
+    command: cli::Command,
+    quiet: bool,
+    format: cli::OutputFormat,
+) {
+    let store = cli_config_store(config);
+    let history = addon_store::app_data_dir().map(operation_history::OperationHistory::new);
+    std::process::exit(cli::execute(&store, command, quiet, format, history))
 }
@@ -0,0 +1,213 @@
+use std::{
+    fs::OpenOptions,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{AddonError, AddonErrorKind},
+    validate,
+};
+
+/// Name of the lock file guarding symlink changes in the AddOns folder
+const FOLDER_LOCK_FILE: &str = ".folder.lock";
+/// Locks older than this are considered stale even if the owner cannot be checked
+const STALE_LOCK_AGE: Duration = Duration::from_secs(60 * 60);
+/// Folder locks are only held briefly, so wait for them instead of failing immediately
+const FOLDER_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Contents of a lock file, used to report the owner and detect stale locks
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LockInfo {
+    pid: u32,
+    operation: String,
+    acquired_at: DateTime<Utc>,
+}
+
+impl LockInfo {
+    fn is_stale(&self) -> bool {
+        if is_process_running(self.pid) == Some(false) {
+            return true;
+        }
+        let age = Utc::now().signed_duration_since(self.acquired_at);
+        age.to_std().is_ok_and(|age| age > STALE_LOCK_AGE)
+    }
+}
+
+/// A lock file in `.addonmanager`, removed when dropped
+#[derive(Debug)]
+pub struct OperationLock {
+    path: PathBuf,
+}
+
+impl OperationLock {
+    /// Lock a single repository in `.addonmanager` for the duration of an operation.
+    /// Fails immediately if another process or operation holds the lock.
+    pub fn acquire_repo(
+        addons_dir: &Path,
+        repo_name: &str,
+        operation: &str,
+    ) -> Result<Self, AddonError> {
+        let manager_dir = validate::ensure_manager_dir(addons_dir)?;
+        let path = manager_dir.join(format!("{repo_name}.lock"));
+        Self::acquire(path, operation, Duration::ZERO)
+    }
+
+    /// Lock the AddOns folder while symlinks are created or removed.
+    /// Waits briefly for other operations to finish before failing.
+    pub fn acquire_folder(addons_dir: &Path, operation: &str) -> Result<Self, AddonError> {
+        let manager_dir = validate::ensure_manager_dir(addons_dir)?;
+        let path = manager_dir.join(FOLDER_LOCK_FILE);
+        Self::acquire(path, operation, FOLDER_LOCK_TIMEOUT)
+    }
+
+    fn acquire(path: PathBuf, operation: &str, timeout: Duration) -> Result<Self, AddonError> {
+        let started = std::time::Instant::now();
+        loop {
+            match Self::try_create(&path, operation) {
+                Ok(()) => return Ok(OperationLock { path }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => {
+                    return Err(AddonError::io("Failed to create lock file", &e).with_path(path));
+                }
+            }
+
+            let info = read_lock_info(&path);
+            let held = match &info {
+                Some(info) => !info.is_stale(),
+                // The owner may not have written the lock contents yet
+                None => is_recently_modified(&path),
+            };
+            if held {
+                if started.elapsed() >= timeout {
+                    let owner = info
+                        .map(|info| {
+                            format!(
+                                " ('{}', pid {}, since {})",
+                                info.operation,
+                                info.pid,
+                                info.acquired_at.to_rfc3339()
+                            )
+                        })
+                        .unwrap_or_default();
+                    return Err(AddonError::new(
+                        AddonErrorKind::Locked,
+                        format!(
+                            "Locked by another operation{owner}. Try again once it has finished."
+                        ),
+                    )
+                    .with_path(path));
+                }
+                std::thread::sleep(LOCK_POLL_INTERVAL);
+                continue;
+            }
+
+            // Stale lock: the owner is gone, take it over
+            eprintln!("Removing stale lock file: {}", path.display());
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => {
+                    return Err(AddonError::io("Failed to remove stale lock", &e).with_path(path));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn try_create(path: &Path, operation: &str) -> std::io::Result<()> {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+        let info = LockInfo {
+            pid: std::process::id(),
+            operation: operation.to_string(),
+            acquired_at: Utc::now(),
+        };
+        let content = serde_json::to_string(&info).map_err(std::io::Error::other)?;
+        file.write_all(content.as_bytes())
+    }
+}
+
+impl Drop for OperationLock {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            eprintln!("Failed to remove lock file {}: {e}", self.path.display());
+        }
+    }
+}
+
+fn read_lock_info(path: &Path) -> Option<LockInfo> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn is_recently_modified(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < Duration::from_secs(5))
+}
+
+/// Returns None when liveness cannot be determined on this platform
+fn is_process_running(pid: u32) -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        Some(Path::new("/proc").join(pid.to_string()).exists())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_addons_dir;
+
+    #[test]
+    fn test_repo_lock_is_exclusive_and_released_on_drop() {
+        let (_temp, addons_dir) = setup_addons_dir();
+
+        let lock = OperationLock::acquire_repo(&addons_dir, "FakeRepo", "install")
+            .expect("Failed to acquire lock");
+        let lock_path = addons_dir.join(".addonmanager").join("FakeRepo.lock");
+        assert!(lock_path.exists(), "Lock file was not created");
+
+        let second = OperationLock::acquire_repo(&addons_dir, "FakeRepo", "update");
+        assert!(second.is_err(), "Second lock should fail while held");
+        assert!(
+            OperationLock::acquire_repo(&addons_dir, "OtherRepo", "update").is_ok(),
+            "Locks on other repos should not conflict"
+        );
+
+        drop(lock);
+        assert!(!lock_path.exists(), "Lock file was not removed on drop");
+        assert!(OperationLock::acquire_repo(&addons_dir, "FakeRepo", "update").is_ok());
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let manager_dir = validate::ensure_manager_dir(&addons_dir).unwrap();
+
+        let stale = LockInfo {
+            pid: std::process::id(),
+            operation: "install".to_string(),
+            acquired_at: Utc::now() - chrono::Duration::hours(2),
+        };
+        std::fs::write(
+            manager_dir.join("FakeRepo.lock"),
+            serde_json::to_string(&stale).unwrap(),
+        )
+        .unwrap();
+
+        let lock = OperationLock::acquire_repo(&addons_dir, "FakeRepo", "update");
+        assert!(lock.is_ok(), "Stale lock was not taken over: {lock:?}");
+    }
+}
@@ -1,7 +1,10 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use addon_gui_lib::cli::{Command, OutputFormat};
+use addon_gui_lib::update::UpdateFilter;
 use clap::Parser;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -9,16 +12,42 @@ struct Args {
     /// Run the application in headless mode
     #[arg(long)]
     headless: bool,
-    /// Suppress output in headless mode
-    #[arg(long, short, requires = "headless")]
+    /// Suppress output in headless mode and subcommands
+    #[arg(long, short, global = true)]
     quiet: bool,
+    /// Print the planned changes without applying them
+    #[arg(long, requires = "headless")]
+    dry_run: bool,
+    /// Only list outdated addons, exits with 4 when updates are available
+    #[arg(long, requires = "headless", conflicts_with = "dry_run")]
+    check: bool,
+    /// Restrict headless updates to some folders or repositories
+    #[command(flatten)]
+    filter: UpdateFilter,
+    /// Config file to use instead of the app's addon-manager.json
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Output format of headless mode and subcommands
+    #[arg(long, global = true, value_enum, default_value = "text")]
+    format: OutputFormat,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
 fn main() {
     let args = Args::parse();
 
-    if args.headless {
-        addon_gui_lib::run_headless(args.quiet)
+    if let Some(command) = args.command {
+        addon_gui_lib::run_cli(args.config, command, args.quiet, args.format)
+    } else if args.headless {
+        addon_gui_lib::run_headless(
+            args.config,
+            args.quiet,
+            args.dry_run,
+            args.check,
+            args.filter,
+            args.format,
+        )
     } else {
         addon_gui_lib::run()
     }
@@ -0,0 +1,353 @@
+use std::{
+    collections::HashMap,
+    fs::OpenOptions,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    error::{AddonError, AddonErrorKind},
+    git,
+    operation_reporter::{OperationEvent, OperationKey, OperationType},
+};
+
+const HISTORY_FILE: &str = "operation-history.jsonl";
+/// Rotate the log once it grows past this size
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+/// Number of rotated logs kept next to the current one
+const MAX_ROTATED_LOGS: usize = 3;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum OperationStatus {
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// A finished operation as persisted in the history log
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationRecord {
+    pub key: OperationKey,
+    pub operation: OperationType,
+    #[ts(type = "string")]
+    pub started_at: DateTime<Utc>,
+    #[ts(type = "string")]
+    pub finished_at: DateTime<Utc>,
+    pub status: OperationStatus,
+    pub messages: Vec<String>,
+    pub warnings: Vec<String>,
+    pub errors: Vec<AddonError>,
+    /// Repository HEAD before the operation started
+    pub ref_before: Option<String>,
+    /// Repository HEAD after the operation finished
+    pub ref_after: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationHistoryFilter {
+    pub folder_path: Option<String>,
+    pub repo_url: Option<String>,
+    /// Only include operations started at or after this RFC 3339 time
+    #[ts(type = "string | null")]
+    pub since: Option<DateTime<Utc>>,
+    /// Only include operations started at or before this RFC 3339 time
+    #[ts(type = "string | null")]
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl OperationHistoryFilter {
+    fn matches(&self, record: &OperationRecord) -> bool {
+        self.folder_path
+            .as_ref()
+            .is_none_or(|f| *f == record.key.folder_path)
+            && self
+                .repo_url
+                .as_ref()
+                .is_none_or(|r| *r == record.key.repo_url)
+            && self.since.is_none_or(|since| record.started_at >= since)
+            && self.until.is_none_or(|until| record.started_at <= until)
+    }
+}
+
+/// Collects operation events per key and appends finished operations
+/// to a JSON Lines log in the app data directory
+pub struct OperationHistory {
+    log_dir: PathBuf,
+    in_flight: Mutex<HashMap<OperationKey, InFlightOperation>>,
+}
+
+struct InFlightOperation {
+    record: OperationRecord,
+    outcome: Option<OperationStatus>,
+}
+
+impl OperationHistory {
+    pub fn new(log_dir: PathBuf) -> Self {
+        OperationHistory {
+            log_dir,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn log_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.log_dir.join(HISTORY_FILE)
+        } else {
+            self.log_dir
+                .join(format!("operation-history.{index}.jsonl"))
+        }
+    }
+
+    /// Track an event for a running operation. Events before `Started` are ignored
+    pub fn record(&self, key: &OperationKey, event: &OperationEvent) {
+        let mut in_flight = self.lock();
+        if let OperationEvent::Started { operation } = event {
+            let now = Utc::now();
+            in_flight.insert(
+                key.clone(),
+                InFlightOperation {
+                    record: OperationRecord {
+                        key: key.clone(),
+                        operation: operation.clone(),
+                        started_at: now,
+                        finished_at: now,
+                        status: OperationStatus::Failed,
+                        messages: Vec::new(),
+                        warnings: Vec::new(),
+                        errors: Vec::new(),
+                        ref_before: read_head_ref(key),
+                        ref_after: None,
+                    },
+                    outcome: None,
+                },
+            );
+            return;
+        }
+
+        let Some(op) = in_flight.get_mut(key) else {
+            return;
+        };
+        match event {
+            OperationEvent::Status(message) => op.record.messages.push(message.clone()),
+            OperationEvent::Warning(message) => op.record.warnings.push(message.clone()),
+            OperationEvent::Error(error) => op.record.errors.push(error.clone()),
+            OperationEvent::Completed => op.outcome = Some(OperationStatus::Completed),
+            OperationEvent::Cancelled => op.outcome = Some(OperationStatus::Cancelled),
+            OperationEvent::Started { .. } | OperationEvent::Progress { .. } => {}
+        }
+    }
+
+    /// Finish a running operation and append it to the log.
+    /// Operations that never emitted `Completed` or `Cancelled` are recorded as failed.
+    pub fn finish(&self, key: &OperationKey) {
+        let mut in_flight = self.lock();
+        let Some(op) = in_flight.remove(key) else {
+            return;
+        };
+        let mut record = op.record;
+        record.finished_at = Utc::now();
+        record.status = op.outcome.unwrap_or(OperationStatus::Failed);
+        record.ref_after = read_head_ref(key);
+
+        // Keep the mutex held while writing so concurrent appends do not interleave
+        if let Err(e) = self.append(&record) {
+            eprintln!("Failed to write operation history: {e}");
+        }
+    }
+
+    fn append(&self, record: &OperationRecord) -> Result<(), AddonError> {
+        std::fs::create_dir_all(&self.log_dir).map_err(|e| {
+            AddonError::io("Failed to create history dir", &e).with_path(&self.log_dir)
+        })?;
+        self.rotate_if_needed()?;
+
+        let path = self.log_path(0);
+        let mut line = serde_json::to_string(record).map_err(|e| {
+            AddonError::new(
+                AddonErrorKind::Other,
+                "Failed to serialize operation record",
+            )
+            .with_cause(e)
+        })?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| AddonError::io("Failed to append history", &e).with_path(&path))
+    }
+
+    fn rotate_if_needed(&self) -> Result<(), AddonError> {
+        let current = self.log_path(0);
+        let size = std::fs::metadata(&current).map(|m| m.len()).unwrap_or(0);
+        if size < MAX_LOG_BYTES {
+            return Ok(());
+        }
+
+        // Shift operation-history.N.jsonl up by one, dropping the oldest
+        for index in (0..MAX_ROTATED_LOGS).rev() {
+            let from = self.log_path(index);
+            if from.exists() {
+                let to = self.log_path(index + 1);
+                std::fs::rename(&from, &to)
+                    .map_err(|e| AddonError::io("Failed to rotate history", &e).with_path(&from))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read all persisted operations matching the filter, oldest first
+    pub fn query(&self, filter: &OperationHistoryFilter) -> Vec<OperationRecord> {
+        let _guard = self.lock();
+        (0..=MAX_ROTATED_LOGS)
+            .rev()
+            .flat_map(|index| read_records(&self.log_path(index)))
+            .filter(|record| filter.matches(record))
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<OperationKey, InFlightOperation>> {
+        self.in_flight.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Mutex poisoned: {poisoned:?}");
+            poisoned.into_inner()
+        })
+    }
+}
+
+fn read_records(path: &Path) -> Vec<OperationRecord> {
+    let Ok(file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(&line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                eprintln!("Skipping malformed history line in {}: {e}", path.display());
+                None
+            }
+        })
+        .collect()
+}
+
+/// HEAD of the Git repository an operation key refers to, if any
+fn read_head_ref(key: &OperationKey) -> Option<String> {
+    let (_owner, repo_name) = git::extract_owner_repo_from_url(&key.repo_url).ok()?;
+    let repo_dir = Path::new(&key.folder_path)
+        .join(".addonmanager")
+        .join(repo_name);
+    let repo = git2::Repository::open(repo_dir).ok()?;
+    let head = repo.head().ok()?;
+    head.target().map(|oid| oid.to_string())
+}
+
+/// Append the finished operation for `key` to the history log
+pub fn finish_operation(app_handle: &tauri::AppHandle, key: &OperationKey) {
+    if let Some(history) = tauri::Manager::try_state::<OperationHistory>(app_handle) {
+        history.finish(key);
+    }
+}
+
+#[tauri::command]
+pub fn get_operation_history(
+    filter: Option<OperationHistoryFilter>,
+    history: tauri::State<'_, OperationHistory>,
+) -> Vec<OperationRecord> {
+    history.query(&filter.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(repo_url: &str, folder_path: &str) -> OperationKey {
+        OperationKey {
+            repo_url: repo_url.to_string(),
+            folder_path: folder_path.to_string(),
+        }
+    }
+
+    fn run_operation(history: &OperationHistory, key: &OperationKey, last: OperationEvent) {
+        history.record(
+            key,
+            &OperationEvent::Started {
+                operation: OperationType::Install,
+            },
+        );
+        history.record(key, &OperationEvent::Status("Cloning repository...".into()));
+        history.record(key, &OperationEvent::Warning("Multiple names".into()));
+        history.record(key, &last);
+        history.finish(key);
+    }
+
+    #[test]
+    fn test_history_records_and_filters_operations() {
+        let temp = tempfile::tempdir().unwrap();
+        let history = OperationHistory::new(temp.path().to_path_buf());
+
+        let a = key("local://A", "/one/AddOns");
+        let b = key("local://B", "/two/AddOns");
+        run_operation(&history, &a, OperationEvent::Completed);
+        run_operation(
+            &history,
+            &b,
+            OperationEvent::Error(AddonError::new(AddonErrorKind::Io, "boom")),
+        );
+
+        let all = history.query(&OperationHistoryFilter::default());
+        assert_eq!(all.len(), 2, "Expected 2 records, found: {all:?}");
+        assert_eq!(all[0].status, OperationStatus::Completed);
+        assert_eq!(all[0].messages, vec!["Cloning repository...".to_string()]);
+        assert_eq!(all[0].warnings.len(), 1);
+        assert_eq!(all[1].status, OperationStatus::Failed);
+        assert_eq!(all[1].errors.len(), 1);
+
+        let filtered = history.query(&OperationHistoryFilter {
+            folder_path: Some("/two/AddOns".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].key, b);
+
+        let future = history.query(&OperationHistoryFilter {
+            since: Some(Utc::now() + chrono::Duration::hours(1)),
+            ..Default::default()
+        });
+        assert!(future.is_empty(), "Time filter should exclude all records");
+    }
+
+    #[test]
+    fn test_history_rotates_by_size() {
+        let temp = tempfile::tempdir().unwrap();
+        let history = OperationHistory::new(temp.path().to_path_buf());
+        let current = temp.path().join(HISTORY_FILE);
+
+        std::fs::write(&current, vec![b'\n'; MAX_LOG_BYTES as usize]).unwrap();
+        run_operation(
+            &history,
+            &key("local://A", "/AddOns"),
+            OperationEvent::Completed,
+        );
+
+        assert!(
+            temp.path().join("operation-history.1.jsonl").exists(),
+            "Log was not rotated"
+        );
+        let records = history.query(&OperationHistoryFilter::default());
+        assert_eq!(records.len(), 1, "Expected only the new record");
+    }
+}
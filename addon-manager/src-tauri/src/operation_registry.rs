@@ -0,0 +1,125 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::operation_reporter::OperationKey;
+
+/// Shared flag used to request cancellation of a running operation.
+/// Cloned into the blocking task and checked from git2 progress callbacks.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Tracks running operations so they can be cancelled from the frontend
+#[derive(Default)]
+pub struct OperationRegistry {
+    operations: Mutex<HashMap<OperationKey, CancellationToken>>,
+}
+
+impl OperationRegistry {
+    /// Register a new operation and return its cancellation token.
+    /// Replaces the token of any previous operation with the same key.
+    pub fn register(&self, key: &OperationKey) -> CancellationToken {
+        let token = CancellationToken::default();
+        let mut map = self.lock();
+        map.insert(key.clone(), token.clone());
+        token
+    }
+
+    /// Remove the operation, unless it was already replaced by a newer one
+    pub fn unregister(&self, key: &OperationKey, token: &CancellationToken) {
+        let mut map = self.lock();
+        if map.get(key).is_some_and(|t| Arc::ptr_eq(&t.0, &token.0)) {
+            map.remove(key);
+        }
+    }
+
+    /// Request cancellation. Returns false if no operation is running for the key
+    pub fn cancel(&self, key: &OperationKey) -> bool {
+        match self.lock().get(key) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<OperationKey, CancellationToken>> {
+        self.operations.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Mutex poisoned: {poisoned:?}");
+            poisoned.into_inner()
+        })
+    }
+}
+
+#[tauri::command]
+pub fn cancel_operation_cmd(
+    repo_url: String,
+    folder_path: String,
+    registry: tauri::State<'_, OperationRegistry>,
+) -> Result<(), String> {
+    let key = OperationKey {
+        repo_url,
+        folder_path,
+    };
+    if registry.cancel(&key) {
+        Ok(())
+    } else {
+        Err(format!(
+            "No running operation for {} in {}",
+            key.repo_url, key.folder_path
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(repo_url: &str) -> OperationKey {
+        OperationKey {
+            repo_url: repo_url.to_string(),
+            folder_path: "/AddOns".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cancel_registered_operation() {
+        let registry = OperationRegistry::default();
+        let token = registry.register(&key("a"));
+
+        assert!(!token.is_cancelled());
+        assert!(registry.cancel(&key("a")));
+        assert!(token.is_cancelled());
+        assert!(!registry.cancel(&key("b")), "Unknown key should not cancel");
+    }
+
+    #[test]
+    fn test_unregister_keeps_newer_operation() {
+        let registry = OperationRegistry::default();
+        let old = registry.register(&key("a"));
+        let new = registry.register(&key("a"));
+
+        registry.unregister(&key("a"), &old);
+        assert!(registry.cancel(&key("a")), "Newer operation was removed");
+        assert!(new.is_cancelled());
+        assert!(!old.is_cancelled());
+
+        registry.unregister(&key("a"), &new);
+        assert!(!registry.cancel(&key("a")));
+    }
+}
@@ -1,7 +1,10 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
 use ts_rs::TS;
 
-#[derive(Debug, Serialize, Clone, TS)]
+use crate::{error::AddonError, operation_history::OperationHistory};
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[ts(export)]
 #[serde(rename_all = "camelCase")]
 pub enum OperationType {
@@ -18,11 +21,12 @@ pub enum OperationEvent {
     Progress { current: usize, total: usize },
     Status(String),
     Warning(String),
-    Error(String),
+    Error(AddonError),
     Completed,
+    Cancelled,
 }
 
-#[derive(Debug, Serialize, Clone, TS, PartialEq, Eq, Hash)]
+#[derive(Debug, Serialize, Deserialize, Clone, TS, PartialEq, Eq, Hash)]
 #[ts(export)]
 #[serde(rename_all = "camelCase")]
 pub struct OperationKey {
@@ -38,6 +42,54 @@ pub struct OperationEventPayload {
     pub event: OperationEvent,
 }
 
+/// Receiver of operation events, so operations can run with or without a Tauri app
+pub trait EventSink {
+    fn operation_event(&self, payload: OperationEventPayload) -> Result<(), String>;
+
+    /// Called once the operation for `key` emitted its last event
+    fn finish_operation(&self, _key: &OperationKey) {}
+}
+
+/// Record the event in the operation history and emit it to the frontend
+impl EventSink for AppHandle {
+    fn operation_event(&self, payload: OperationEventPayload) -> Result<(), String> {
+        if let Some(history) = self.try_state::<OperationHistory>() {
+            history.record(&payload.key, &payload.event);
+        }
+        self.emit("operation-event", payload)
+            .map_err(|e| e.to_string())
+    }
+
+    fn finish_operation(&self, key: &OperationKey) {
+        crate::operation_history::finish_operation(self, key);
+    }
+}
+
+/// Prints warnings and errors to stderr, for operations without a frontend
+pub struct StderrEvents;
+
+impl EventSink for StderrEvents {
+    fn operation_event(&self, payload: OperationEventPayload) -> Result<(), String> {
+        match payload.event {
+            OperationEvent::Warning(message) => eprintln!("Warning: {message}"),
+            OperationEvent::Error(error) => eprintln!("Error: {error}"),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+pub fn emit_operation_event(
+    events: &(impl EventSink + ?Sized),
+    key: &OperationKey,
+    event: OperationEvent,
+) -> Result<(), String> {
+    events.operation_event(OperationEventPayload {
+        key: key.clone(),
+        event,
+    })
+}
+
 pub struct OperationReporter {
     pub key: OperationKey,
     pub event_fn: Box<dyn FnMut(OperationEvent) + Send>,
@@ -66,11 +118,15 @@ impl OperationReporter {
         (self.event_fn)(OperationEvent::Warning(message.into()));
     }
 
-    pub fn error<S: Into<String>>(&mut self, message: S) {
-        (self.event_fn)(OperationEvent::Error(message.into()));
+    pub fn error(&mut self, error: AddonError) {
+        (self.event_fn)(OperationEvent::Error(error));
     }
 
     pub fn completed(&mut self) {
         (self.event_fn)(OperationEvent::Completed);
     }
+
+    pub fn cancelled(&mut self) {
+        (self.event_fn)(OperationEvent::Cancelled);
+    }
 }
@@ -0,0 +1,296 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+use crate::{deploy, error::AddonError, game_process, lock::OperationLock, symlink, trash};
+
+/// A symlink in the AddOns folder that does not belong to a managed repository
+#[derive(Debug, Serialize, Deserialize, Clone, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanLink {
+    /// Symlink name in the AddOns folder
+    pub name: String,
+    /// Resolved link target
+    pub target: String,
+}
+
+/// Leftovers of repositories removed or linked outside of the manager
+#[derive(Debug, Serialize, Deserialize, Clone, TS, Default, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanReport {
+    /// Links whose target no longer exists
+    pub broken_links: Vec<OrphanLink>,
+    /// Links pointing outside of `.addonmanager`
+    pub foreign_links: Vec<OrphanLink>,
+    /// Repositories in `.addonmanager` without any link in the AddOns folder
+    pub unlinked_repos: Vec<String>,
+    /// Entries a cleanup could not fix, with the reason
+    pub errors: Vec<String>,
+}
+
+impl OrphanReport {
+    pub fn is_empty(&self) -> bool {
+        self.broken_links.is_empty()
+            && self.foreign_links.is_empty()
+            && self.unlinked_repos.is_empty()
+    }
+}
+
+/// Which kinds of orphans `cleanup_orphans` should fix
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanCleanup {
+    /// Remove broken links
+    pub broken_links: bool,
+    /// Remove links pointing outside of `.addonmanager`; their targets are left alone
+    pub foreign_links: bool,
+    /// Move repositories without links into the trash
+    pub unlinked_repos: bool,
+}
+
+impl Default for OrphanCleanup {
+    fn default() -> Self {
+        OrphanCleanup {
+            broken_links: true,
+            foreign_links: false,
+            unlinked_repos: false,
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Detect broken links, links outside `.addonmanager` and repositories without links
+pub fn scan_orphans(addons_dir: &Path) -> OrphanReport {
+    let manager_dir = addons_dir.join(".addonmanager");
+    let mut report = OrphanReport::default();
+    let mut linked_repos = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(addons_dir) {
+        for link in entries.flatten().map(|entry| entry.path()) {
+            if !symlink::is_addon_symlinked(&link) {
+                continue;
+            }
+            let Some(target) = symlink::resolve_link_target(&link) else {
+                continue;
+            };
+            let orphan = OrphanLink {
+                name: file_name(&link),
+                target: target.to_string_lossy().to_string(),
+            };
+            if symlink::is_broken_link(&link) {
+                report.broken_links.push(orphan);
+            } else if let Ok(relative) = target.strip_prefix(&manager_dir) {
+                if let Some(repo) = relative.components().next() {
+                    linked_repos.push(PathBuf::from(repo.as_os_str()));
+                }
+            } else {
+                report.foreign_links.push(orphan);
+            }
+        }
+    }
+
+    // Copies and hardlink trees are real directories tracked by a manifest
+    linked_repos.extend(
+        deploy::list_deployments(addons_dir)
+            .iter()
+            .map(|manifest| PathBuf::from(deploy::manifest_repo(manifest))),
+    );
+
+    if let Ok(entries) = fs::read_dir(&manager_dir) {
+        report.unlinked_repos = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir() && !file_name(path).starts_with('.'))
+            .map(|path| file_name(&path))
+            .filter(|name| !linked_repos.iter().any(|repo| repo == Path::new(name)))
+            .collect();
+    }
+
+    report.broken_links.sort_by(|a, b| a.name.cmp(&b.name));
+    report.foreign_links.sort_by(|a, b| a.name.cmp(&b.name));
+    report.unlinked_repos.sort();
+    report
+}
+
+/// Key the repository is known by, used to label it in the trash
+fn repo_key(repo_dir: &Path) -> String {
+    git2::Repository::open(repo_dir)
+        .ok()
+        .and_then(|repo| repo.find_remote("origin").ok()?.url().map(str::to_string))
+        .unwrap_or_else(|| format!("local://{}", file_name(repo_dir)))
+}
+
+/// Fix the selected kinds of orphans and return what was cleaned up.
+/// Failures for single entries are collected in `errors` and skipped.
+pub fn cleanup_orphans(
+    addons_dir: &Path,
+    cleanup: OrphanCleanup,
+) -> Result<OrphanReport, AddonError> {
+    let _lock = OperationLock::acquire_folder(addons_dir, "cleanup")?;
+    let found = scan_orphans(addons_dir);
+    let mut cleaned = OrphanReport::default();
+
+    let links = found
+        .broken_links
+        .into_iter()
+        .filter(|_| cleanup.broken_links)
+        .map(|link| (link, true))
+        .chain(
+            found
+                .foreign_links
+                .into_iter()
+                .filter(|_| cleanup.foreign_links)
+                .map(|link| (link, false)),
+        );
+    for (link, broken) in links {
+        let path = addons_dir.join(&link.name);
+        match fs::remove_file(&path).or_else(|_| fs::remove_dir(&path)) {
+            Ok(()) if broken => cleaned.broken_links.push(link),
+            Ok(()) => cleaned.foreign_links.push(link),
+            Err(e) => cleaned
+                .errors
+                .push(format!("Failed to remove link {}: {e}", path.display())),
+        }
+    }
+
+    if cleanup.unlinked_repos && !found.unlinked_repos.is_empty() {
+        game_process::ensure_not_running(addons_dir, "removing addons", &mut |_| {})?;
+        for repo_name in found.unlinked_repos {
+            let repo_dir = addons_dir.join(".addonmanager").join(&repo_name);
+            let trashed = OperationLock::acquire_repo(addons_dir, &repo_name, "delete").and_then(
+                |_repo_lock| {
+                    trash::move_to_trash(addons_dir, &repo_name, &repo_key(&repo_dir), Vec::new())
+                },
+            );
+            match trashed {
+                Ok(_) => cleaned.unlinked_repos.push(repo_name),
+                Err(e) => cleaned
+                    .errors
+                    .push(format!("Failed to trash {}: {e}", repo_dir.display())),
+            }
+        }
+    }
+    Ok(cleaned)
+}
+
+#[tauri::command]
+pub async fn scan_orphans_cmd(path: String) -> Result<OrphanReport, String> {
+    Ok(scan_orphans(Path::new(&path)))
+}
+
+#[tauri::command]
+pub async fn cleanup_orphans_cmd(
+    app_handle: AppHandle,
+    path: String,
+    cleanup: Option<OrphanCleanup>,
+) -> Result<OrphanReport, String> {
+    let cleanup = cleanup.unwrap_or_default();
+    let cleaned =
+        tauri::async_runtime::spawn_blocking(move || cleanup_orphans(Path::new(&path), cleanup))
+            .await
+            .map_err(|e| format!("Task join error: {e}"))??;
+
+    app_handle
+        .emit("addon-data-updated", ())
+        .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))?;
+    Ok(cleaned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{print_dir_tree, setup_addons_dir};
+    use crate::validate;
+
+    #[test]
+    fn test_scan_and_cleanup_orphans() {
+        let (temp, addons_dir) = setup_addons_dir();
+        let manager_dir = validate::ensure_manager_dir(&addons_dir).unwrap();
+        for repo in ["LinkedRepo", "UnlinkedRepo", "DeletedRepo"] {
+            fs::create_dir_all(manager_dir.join(repo).join("SubAddon")).unwrap();
+        }
+        let outside = temp.path().join("DevAddon");
+        fs::create_dir_all(&outside).unwrap();
+
+        symlink::create_symlink(
+            manager_dir.join("LinkedRepo/SubAddon"),
+            addons_dir.join("Linked"),
+        )
+        .unwrap();
+        symlink::create_symlink(
+            manager_dir.join("DeletedRepo/SubAddon"),
+            addons_dir.join("Broken"),
+        )
+        .unwrap();
+        symlink::create_symlink(&outside, addons_dir.join("DevAddon")).unwrap();
+        fs::remove_dir_all(manager_dir.join("DeletedRepo")).unwrap();
+        print_dir_tree(addons_dir.to_str().unwrap());
+
+        let report = scan_orphans(&addons_dir);
+        assert_eq!(
+            report
+                .broken_links
+                .iter()
+                .map(|l| &l.name)
+                .collect::<Vec<_>>(),
+            vec!["Broken"]
+        );
+        assert_eq!(
+            report
+                .foreign_links
+                .iter()
+                .map(|l| &l.name)
+                .collect::<Vec<_>>(),
+            vec!["DevAddon"]
+        );
+        assert_eq!(report.unlinked_repos, vec!["UnlinkedRepo".to_string()]);
+        assert!(symlink::is_addon_symlinked(addons_dir.join("Broken")));
+
+        let cleaned = cleanup_orphans(&addons_dir, OrphanCleanup::default()).unwrap();
+        assert_eq!(cleaned.broken_links, report.broken_links);
+        assert!(cleaned.foreign_links.is_empty() && cleaned.unlinked_repos.is_empty());
+        assert!(!addons_dir.join("Broken").is_symlink());
+        assert!(
+            addons_dir.join("DevAddon").is_symlink(),
+            "Foreign links are kept by default"
+        );
+
+        let remaining = scan_orphans(&addons_dir);
+        assert!(remaining.broken_links.is_empty());
+        assert_eq!(remaining.unlinked_repos, report.unlinked_repos);
+
+        let trash_repos = OrphanCleanup {
+            broken_links: false,
+            foreign_links: false,
+            unlinked_repos: true,
+        };
+        let lock = OperationLock::acquire_repo(&addons_dir, "UnlinkedRepo", "update").unwrap();
+        let cleaned = cleanup_orphans(&addons_dir, trash_repos).unwrap();
+        assert!(cleaned.unlinked_repos.is_empty());
+        assert_eq!(
+            cleaned.errors.len(),
+            1,
+            "Locked repo should fail: {cleaned:?}"
+        );
+        assert!(manager_dir.join("UnlinkedRepo").exists());
+
+        drop(lock);
+        let cleaned = cleanup_orphans(&addons_dir, trash_repos).unwrap();
+        assert_eq!(cleaned.unlinked_repos, report.unlinked_repos);
+        assert!(cleaned.errors.is_empty());
+        assert!(!manager_dir.join("UnlinkedRepo").exists());
+    }
+}
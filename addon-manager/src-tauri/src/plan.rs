@@ -0,0 +1,366 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::{
+    addon_discovery::AppState,
+    addon_disk,
+    addon_store::{self, AddOnsUserConfig},
+    error::{AddonError, AddonErrorKind},
+    git,
+    operation_reporter::{OperationKey, OperationType},
+    remove, symlink, trash,
+    update::{self, UpdateFilter},
+};
+
+/// A single change an operation would make, computed without touching disk
+#[derive(Debug, Serialize, Clone, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum PlannedAction {
+    /// Clone `url` into `path`
+    Clone { url: String, path: String },
+    /// Remove an existing directory that is in the way
+    ReplaceDirectory { path: String },
+    /// Create symlink `link` pointing to `target`
+    CreateLink { link: String, target: String },
+    /// Link every sub-addon found after cloning; names are only known once cloned
+    LinkDiscoveredAddons { path: String },
+    /// Remove symlink `link` currently pointing to `target`
+    RemoveLink { link: String, target: String },
+    /// Fetch `branch` from origin
+    Fetch { url: String, branch: String },
+    /// Force reset `branch` from one commit to another
+    ResetBranch {
+        branch: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// Move a repository directory into the trash at `destination`
+    MoveToTrash { path: String, destination: String },
+}
+
+/// Actions an operation would perform for a single repository
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationPlan {
+    pub key: OperationKey,
+    pub operation: OperationType,
+    pub actions: Vec<PlannedAction>,
+}
+
+impl fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let short = |oid: &Option<String>| match oid {
+            Some(oid) => oid.chars().take(7).collect(),
+            None => "unknown".to_string(),
+        };
+        match self {
+            PlannedAction::Clone { url, path } => write!(f, "clone {url} into {path}"),
+            PlannedAction::ReplaceDirectory { path } => write!(f, "replace directory {path}"),
+            PlannedAction::CreateLink { link, target } => {
+                write!(f, "create link {link} -> {target}")
+            }
+            PlannedAction::LinkDiscoveredAddons { path } => {
+                write!(f, "link sub-addons discovered in {path}")
+            }
+            PlannedAction::RemoveLink { link, target } => {
+                write!(f, "remove link {link} -> {target}")
+            }
+            PlannedAction::Fetch { url, branch } => write!(f, "fetch {branch} from {url}"),
+            PlannedAction::ResetBranch { branch, from, to } => {
+                write!(f, "reset {branch} from {} to {}", short(from), short(to))
+            }
+            PlannedAction::MoveToTrash { path, destination } => {
+                write!(f, "move {path} to {destination}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for OperationPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {} in {}",
+            self.operation, self.key.repo_url, self.key.folder_path
+        )?;
+        if self.actions.is_empty() {
+            write!(f, "\n  (nothing to do)")?;
+        }
+        for action in &self.actions {
+            write!(f, "\n  - {action}")?;
+        }
+        Ok(())
+    }
+}
+
+fn display(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// Links `install_sub_addons` would create, replacing whatever is at the link path
+fn link_actions(
+    addons: &[addon_disk::DiskAddon],
+    repo_root: &Path,
+    addons_dir: &Path,
+) -> Vec<PlannedAction> {
+    let mut actions = Vec::new();
+    for addon in addons {
+        let target = if addon.dir == "." {
+            repo_root.to_path_buf()
+        } else {
+            repo_root.join(&addon.dir)
+        };
+        let link = addons_dir.join(&addon.name);
+        if link.exists() || link.is_symlink() {
+            actions.push(PlannedAction::ReplaceDirectory {
+                path: display(&link),
+            });
+        }
+        actions.push(PlannedAction::CreateLink {
+            link: display(&link),
+            target: display(&target),
+        });
+    }
+    actions
+}
+
+/// Plan `install::install_addon`
+pub fn plan_install(url: &str, dir: &str) -> Result<OperationPlan, AddonError> {
+    let addons_dir = Path::new(dir);
+    let (_owner, repo_name) = git::extract_owner_repo_from_url(url).map_err(|e| {
+        AddonError::new(AddonErrorKind::InvalidInput, "Invalid repo URL")
+            .with_url(url)
+            .with_cause(e)
+    })?;
+    let repo_path = addons_dir.join(".addonmanager").join(&repo_name);
+
+    let mut actions = Vec::new();
+    // Reinstalling over an existing checkout: predict links from what is on disk now
+    let existing_addons = if repo_path.exists() {
+        actions.push(PlannedAction::ReplaceDirectory {
+            path: display(&repo_path),
+        });
+        addon_disk::find_all_sub_addons(&repo_path).ok()
+    } else {
+        None
+    };
+    actions.push(PlannedAction::Clone {
+        url: url.to_string(),
+        path: display(&repo_path),
+    });
+    match existing_addons {
+        Some(addons) if !addons.is_empty() => {
+            actions.extend(link_actions(&addons, &repo_path, addons_dir))
+        }
+        _ => actions.push(PlannedAction::LinkDiscoveredAddons {
+            path: display(&repo_path),
+        }),
+    }
+
+    Ok(OperationPlan {
+        key: OperationKey {
+            repo_url: url.to_string(),
+            folder_path: dir.to_string(),
+        },
+        operation: OperationType::Install,
+        actions,
+    })
+}
+
+/// Plan `update::update_addon_repo`. The target commit is the last fetched
+/// remote ref, since fetching would modify the repository.
+pub fn plan_update(path: &str, url: &str, branch: &str) -> Result<OperationPlan, AddonError> {
+    let repo_name = remove::repo_name_from_key(url)?;
+    let repo_dir = Path::new(path).join(".addonmanager").join(repo_name);
+    let repo = git2::Repository::open(&repo_dir).map_err(|e| {
+        AddonError::git("Failed to open repo", &e)
+            .with_url(url)
+            .with_path(&repo_dir)
+    })?;
+
+    let branch_name = branch.strip_prefix("origin/").unwrap_or(branch);
+    let from = repo
+        .head()
+        .ok()
+        .and_then(|h| h.target())
+        .map(|oid| oid.to_string());
+    let to = repo
+        .find_reference(&format!("refs/remotes/origin/{branch_name}"))
+        .ok()
+        .and_then(|r| r.target())
+        .map(|oid| oid.to_string());
+
+    let mut actions = vec![PlannedAction::Fetch {
+        url: url.to_string(),
+        branch: branch_name.to_string(),
+    }];
+    if from != to || to.is_none() {
+        actions.push(PlannedAction::ResetBranch {
+            branch: branch_name.to_string(),
+            from,
+            to,
+        });
+    }
+
+    Ok(OperationPlan {
+        key: OperationKey {
+            repo_url: url.to_string(),
+            folder_path: path.to_string(),
+        },
+        operation: OperationType::Update,
+        actions,
+    })
+}
+
+/// Plan `remove::delete_addon_files`
+pub fn plan_delete(url: &str, path: &str) -> Result<OperationPlan, AddonError> {
+    let addons_dir = PathBuf::from(path);
+    let repo_name = remove::repo_name_from_key(url)?;
+    let repo_dir = addons_dir.join(".addonmanager").join(&repo_name);
+
+    let mut actions = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(&addons_dir) {
+        for entry in entries.flatten() {
+            let link = entry.path();
+            if let Some(target) = symlink::resolve_link_target(&link)
+                && target.starts_with(&repo_dir)
+            {
+                actions.push(PlannedAction::RemoveLink {
+                    link: display(&link),
+                    target: display(&target),
+                });
+            }
+        }
+    }
+    actions.sort_by_key(ToString::to_string);
+    if repo_dir.exists() {
+        actions.push(PlannedAction::MoveToTrash {
+            path: display(&repo_dir),
+            destination: display(&trash::trash_dir(&addons_dir)),
+        });
+    }
+
+    Ok(OperationPlan {
+        key: OperationKey {
+            repo_url: url.to_string(),
+            folder_path: path.to_string(),
+        },
+        operation: OperationType::Delete,
+        actions,
+    })
+}
+
+/// Plan `update::update_all_addons_cmd` from the last scanned disk state
+pub fn plan_update_all(
+    state: &AppState,
+    config: &AddOnsUserConfig,
+    filter: &UpdateFilter,
+) -> Result<Vec<OperationPlan>, AddonError> {
+    let disk_state = state
+        .get_disk_state()
+        .map_err(|e| AddonError::new(AddonErrorKind::Other, "Disk state error").with_cause(e))?;
+    update::pending_updates(&disk_state, config, filter)
+        .into_iter()
+        .map(|(path, url, branch)| plan_update(&path, &url, &branch))
+        .collect()
+}
+
+#[tauri::command]
+pub async fn plan_install_cmd(url: String, path: String) -> Result<OperationPlan, String> {
+    plan_install(&url, &path).map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn plan_update_cmd(
+    url: String,
+    path: String,
+    branch: String,
+) -> Result<OperationPlan, String> {
+    plan_update(&path, &url, &branch).map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn plan_delete_cmd(url: String, path: String) -> Result<OperationPlan, String> {
+    plan_delete(&url, &path).map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn plan_update_all_cmd(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    filter: Option<UpdateFilter>,
+) -> Result<Vec<OperationPlan>, String> {
+    let config = addon_store::load_user_config(&app_handle)?;
+    plan_update_all(&state, &config, &filter.unwrap_or_default()).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_utils::{print_dir_tree, setup_addons_dir};
+    use crate::validate;
+
+    #[test]
+    fn test_plan_delete_lists_links_without_touching_disk() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let manager_dir = validate::ensure_manager_dir(&addons_dir).unwrap();
+        let repo_dir = manager_dir.join("FakeRepo");
+        fs::create_dir_all(repo_dir.join("SubAddon")).unwrap();
+        let link = addons_dir.join("SubAddon");
+        crate::symlink::create_symlink(repo_dir.join("SubAddon"), &link).unwrap();
+        print_dir_tree(addons_dir.to_str().unwrap());
+
+        let plan = plan_delete(
+            "https://github.com/owner/FakeRepo.git",
+            addons_dir.to_str().unwrap(),
+        )
+        .unwrap();
+        println!("{plan}");
+
+        assert_eq!(
+            plan.actions,
+            vec![
+                PlannedAction::RemoveLink {
+                    link: display(&link),
+                    target: display(&repo_dir.join("SubAddon")),
+                },
+                PlannedAction::MoveToTrash {
+                    path: display(&repo_dir),
+                    destination: display(&trash::trash_dir(&addons_dir)),
+                },
+            ]
+        );
+        assert!(link.is_symlink(), "Planning must not remove links");
+        assert!(repo_dir.exists(), "Planning must not remove the repo");
+    }
+
+    #[test]
+    fn test_plan_install_new_repo() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let url = "https://github.com/owner/NewRepo.git";
+
+        let plan = plan_install(url, addons_dir.to_str().unwrap()).unwrap();
+        let repo_path = display(&addons_dir.join(".addonmanager").join("NewRepo"));
+
+        assert_eq!(
+            plan.actions,
+            vec![
+                PlannedAction::Clone {
+                    url: url.to_string(),
+                    path: repo_path.clone(),
+                },
+                PlannedAction::LinkDiscoveredAddons { path: repo_path },
+            ]
+        );
+        assert!(!addons_dir.join(".addonmanager").join("NewRepo").exists());
+    }
+}
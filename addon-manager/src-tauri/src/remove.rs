@@ -1,100 +1,211 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use tauri::{AppHandle, Emitter};
 
-use crate::{git, operation_reporter::*};
-
-/// Deletes addon repo and symlinks by repo URL/key and AddOns path
-/// For Git repos: url is like "https://github.com/owner/repo.git"
-/// For Local repos: url is like "local://folder_name" or the actual path
-pub fn delete_addon_files(url: &str, path: &str) -> Result<(), String> {
-    let addons_dir = PathBuf::from(path);
-    let manager_root = addons_dir.join(".addonmanager");
-
+use crate::{
+    deploy,
+    error::{AddonError, AddonErrorKind},
+    game_process, git,
+    lock::OperationLock,
+    operation_history::finish_operation,
+    operation_reporter::*,
+    saved_variables::{self, SavedVariablesAction},
+    symlink,
+    trash::{self, TrashEntry, TrashedLink},
+};
+
+/// Name of the repository directory in `.addonmanager` for a repo URL/key
+pub(crate) fn repo_name_from_key(url: &str) -> Result<String, AddonError> {
     // Determine if this is a Git repo or local folder
     let repo_name = if url.starts_with("local://") {
         // Extract folder name from local:// URL
         url.strip_prefix("local://")
-            .ok_or("Invalid local URL format")?
+            .ok_or_else(|| {
+                AddonError::new(AddonErrorKind::InvalidInput, "Invalid local URL format")
+                    .with_url(url)
+            })?
             .to_string()
     } else if url.starts_with('/') || url.starts_with("C:") || url.starts_with("\\") {
         // Direct path - extract folder name
         PathBuf::from(url)
             .file_name()
-            .ok_or("Invalid path format")?
+            .ok_or_else(|| {
+                AddonError::new(AddonErrorKind::InvalidInput, "Invalid path format").with_path(url)
+            })?
             .to_string_lossy()
             .to_string()
     } else {
         // Git URL - extract repo name
-        let (_owner, name) =
-            git::extract_owner_repo_from_url(url).map_err(|e| format!("Invalid repo URL: {e}"))?;
+        let (_owner, name) = git::extract_owner_repo_from_url(url).map_err(|e| {
+            AddonError::new(AddonErrorKind::InvalidInput, "Invalid repo URL")
+                .with_url(url)
+                .with_cause(e)
+        })?;
         name
     };
+    Ok(repo_name)
+}
+
+/// Removes addon symlinks and moves the repo into the trash by repo URL/key and AddOns path
+/// For Git repos: url is like "https://github.com/owner/repo.git"
+/// For Local repos: url is like "local://folder_name" or the actual path
+/// Saved variables of the repo's sub-addons in `WTF` are kept, removed or archived
+/// Returns the trash entry, or None if the repo directory did not exist
+pub fn delete_addon_files<F>(
+    url: &str,
+    path: &str,
+    saved_variables: SavedVariablesAction,
+    mut reporter: F,
+) -> Result<Option<TrashEntry>, AddonError>
+where
+    F: FnMut(OperationEvent),
+{
+    let addons_dir = PathBuf::from(path);
+    game_process::ensure_not_running(&addons_dir, "removing addons", &mut reporter)?;
+    let manager_root = addons_dir.join(".addonmanager");
+
+    let repo_name = repo_name_from_key(url)?;
+
+    let _lock = OperationLock::acquire_repo(&addons_dir, &repo_name, "delete")?;
 
-    // Remove any symlinks in AddOns whose target is inside this repo
     let repo_dir = manager_root.join(&repo_name);
+    // Resolve saved variables from the TOC files before the repo is moved away
+    let saved_variable_files = match saved_variables {
+        SavedVariablesAction::Keep => Vec::new(),
+        _ => saved_variables::find_repo_saved_variables(&addons_dir, &repo_dir),
+    };
+
+    // Remove any symlinks in AddOns whose target is inside this repo
+    let folder_lock = OperationLock::acquire_folder(&addons_dir, "unlink")?;
+    let mut links = Vec::new();
     if let Ok(entries) = fs::read_dir(&addons_dir) {
         for entry in entries.flatten() {
             if let Ok(file_type) = entry.file_type()
                 && file_type.is_symlink()
             {
                 let p = entry.path();
-                if let Ok(target) = fs::read_link(&p)
-                    && target.starts_with(&repo_dir)
+                if let Some(target) = symlink::resolve_link_target(&p)
+                    && let Ok(relative) = target.strip_prefix(&repo_dir)
                 {
-                    let _ = fs::remove_file(&p);
+                    fs::remove_file(&p).map_err(|e| {
+                        AddonError::io("Failed to remove symlink", &e).with_path(&p)
+                    })?;
+                    let relative = relative.to_string_lossy();
+                    links.push(TrashedLink {
+                        name: entry.file_name().to_string_lossy().to_string(),
+                        target: if relative.is_empty() {
+                            ".".to_string()
+                        } else {
+                            relative.to_string()
+                        },
+                    });
                 }
             }
         }
     }
-    // Remove repository/folder directory
-    if repo_dir.exists() {
-        let _ = fs::remove_dir_all(&repo_dir);
+    // Copies and hardlink trees of the repo are removed file by file
+    for manifest in deploy::list_deployments(&addons_dir)
+        .into_iter()
+        .filter(|m| deploy::manifest_repo(m) == repo_name)
+    {
+        deploy::remove_deployment(&addons_dir, &manifest.name)?;
+        let relative = manifest
+            .source
+            .split_once('/')
+            .map(|(_, dir)| dir.to_string())
+            .unwrap_or_default();
+        links.push(TrashedLink {
+            name: manifest.name,
+            target: if relative.is_empty() {
+                ".".to_string()
+            } else {
+                relative
+            },
+        });
+    }
+    drop(folder_lock);
+    // Move repository/folder directory into the trash so it can be restored
+    if !repo_dir.exists() {
+        return Ok(None);
+    }
+    let entry = trash::move_to_trash(&addons_dir, &repo_name, url, links)?;
+
+    if !saved_variable_files.is_empty() {
+        reporter(OperationEvent::Status(format!(
+            "Found {} saved variable files",
+            saved_variable_files.len()
+        )));
     }
-    Ok(())
+    saved_variables::dispose_saved_variables(
+        &addons_dir,
+        &repo_name,
+        &saved_variable_files,
+        saved_variables,
+        &mut reporter,
+    );
+    Ok(Some(entry))
+}
+
+/// Saved variable files that deleting the repo could remove or archive
+pub fn find_saved_variables(url: &str, path: &str) -> Result<Vec<PathBuf>, AddonError> {
+    let addons_dir = PathBuf::from(path);
+    let repo_dir = addons_dir
+        .join(".addonmanager")
+        .join(repo_name_from_key(url)?);
+    Ok(saved_variables::find_repo_saved_variables(
+        &addons_dir,
+        &repo_dir,
+    ))
 }
 
 async fn perform_delete_op(
     app_handle: &AppHandle,
     url: String,
     path: String,
+    saved_variables: SavedVariablesAction,
 ) -> Result<(), String> {
     let operation_key = OperationKey {
         repo_url: url.clone(),
         folder_path: path.clone(),
     };
 
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key.clone(),
-                event: OperationEvent::Started {
-                    operation: OperationType::Delete,
-                },
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
-
-    let result = tauri::async_runtime::spawn_blocking(move || delete_addon_files(&url, &path))
-        .await
-        .map_err(|e| format!("Task join error: {e}"))?;
+    emit_operation_event(
+        app_handle,
+        &operation_key,
+        OperationEvent::Started {
+            operation: OperationType::Delete,
+        },
+    )
+    .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+
+    let task_handle = app_handle.clone();
+    let task_key = operation_key.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let result = delete_addon_files(&url, &path, saved_variables, |event| {
+            if let Err(e) = emit_operation_event(&task_handle, &task_key, event) {
+                eprintln!("Failed to emit operation-event: {e}");
+            }
+        });
+        trash::purge_expired_with_config(&task_handle, Path::new(&path));
+        result
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?;
 
     let completion_event = match &result {
         Ok(_) => OperationEvent::Completed,
         Err(e) => OperationEvent::Error(e.clone()),
     };
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key,
-                event: completion_event,
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+    if let Err(e) = emit_operation_event(app_handle, &operation_key, completion_event) {
+        eprintln!("Failed to emit operation-event: {e}");
+    }
 
-    result
+    finish_operation(app_handle, &operation_key);
+
+    result.map(|_| ()).map_err(Into::into)
 }
 
 #[tauri::command]
@@ -102,8 +213,10 @@ pub async fn delete_addon_cmd(
     app_handle: AppHandle,
     url: String,
     path: String,
+    saved_variables: Option<SavedVariablesAction>,
 ) -> Result<(), String> {
-    let result = perform_delete_op(&app_handle, url, path).await;
+    let result =
+        perform_delete_op(&app_handle, url, path, saved_variables.unwrap_or_default()).await;
 
     app_handle
         .emit("addon-data-updated", ())
@@ -112,12 +225,22 @@ pub async fn delete_addon_cmd(
     result
 }
 
+#[tauri::command]
+pub async fn find_saved_variables_cmd(url: String, path: String) -> Result<Vec<String>, String> {
+    let files = find_saved_variables(&url, &path)?;
+    Ok(files
+        .iter()
+        .map(|file| file.to_string_lossy().to_string())
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::addon_disk;
     use crate::git;
     use crate::install;
+    use crate::operation_registry::CancellationToken;
     use crate::test_utils::{print_dir_tree, setup_addons_dir};
     use crate::validate;
 
@@ -149,7 +272,7 @@ mod tests {
         let url = format!("https://github.com/owner/{repo_name}.git");
         let path = addons_dir.to_str().unwrap().to_string();
         // Call delete_addon_files directly
-        let result = delete_addon_files(&url, &path);
+        let result = delete_addon_files(&url, &path, SavedVariablesAction::Keep, |_| {});
         assert!(result.is_ok(), "delete_addon_files failed: {result:?}");
 
         println!("After delete_addon_files:");
@@ -177,7 +300,14 @@ mod tests {
         let url = "https://github.com/sogladev/addon-335-train-all-button.git".to_string();
 
         // Perform real installation
-        let install_result = install::install_addon(url.clone(), addons_path.clone(), |_| {});
+        let install_result = install::install_addon(
+            url.clone(),
+            addons_path.clone(),
+            crate::deploy::LinkOptions::default(),
+            None,
+            &CancellationToken::default(),
+            |_| {},
+        );
         assert!(
             install_result.is_ok(),
             "install_addon failed: {:?}",
@@ -214,7 +344,8 @@ mod tests {
         print_dir_tree(addons_dir.to_str().unwrap());
 
         // Perform removal
-        let remove_result = delete_addon_files(&url, &addons_path);
+        let remove_result =
+            delete_addon_files(&url, &addons_path, SavedVariablesAction::Keep, |_| {});
         assert!(
             remove_result.is_ok(),
             "delete_addon_files failed: {:?}",
@@ -289,7 +420,8 @@ mod tests {
 
         // Delete using local:// URL format
         let local_url = format!("local://{}", local_folder_name);
-        let result = delete_addon_files(&local_url, &addons_path);
+        let result =
+            delete_addon_files(&local_url, &addons_path, SavedVariablesAction::Keep, |_| {});
         assert!(result.is_ok(), "delete_addon_files failed: {:?}", result);
 
         println!("After delete_addon_files (local):");
@@ -309,6 +441,38 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_delete_local_folder_with_relative_link() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let manager_dir = validate::ensure_manager_dir(&addons_dir).unwrap();
+        let local_folder_path = manager_dir.join("RelativeAddon");
+        fs::create_dir_all(&local_folder_path).unwrap();
+        fs::write(local_folder_path.join("RelativeAddon.toc"), "").unwrap();
+
+        let symlink_path = addons_dir.join("RelativeAddon");
+        symlink::create_addon_link(
+            &local_folder_path,
+            &symlink_path,
+            symlink::LinkStyle::Relative,
+        )
+        .unwrap();
+        assert!(fs::read_link(&symlink_path).unwrap().is_relative());
+
+        let entry = delete_addon_files(
+            "local://RelativeAddon",
+            addons_dir.to_str().unwrap(),
+            SavedVariablesAction::Keep,
+            |_| {},
+        )
+        .unwrap()
+        .expect("Expected a trash entry");
+        print_dir_tree(addons_dir.to_str().unwrap());
+
+        assert!(!symlink_path.is_symlink(), "Relative link was not removed");
+        assert_eq!(entry.links.len(), 1, "Relative link was not recorded");
+    }
+
     #[test]
     fn test_delete_local_folder_with_path_format() {
         let (_temp, addons_dir) = setup_addons_dir();
@@ -337,7 +501,12 @@ mod tests {
             .expect("Failed to create symlink");
 
         // Delete using path format (as stored in DiskAddonSource::Local)
-        let result = delete_addon_files(local_folder_path.to_str().unwrap(), &addons_path);
+        let result = delete_addon_files(
+            local_folder_path.to_str().unwrap(),
+            &addons_path,
+            SavedVariablesAction::Keep,
+            |_| {},
+        );
         assert!(result.is_ok(), "delete_addon_files failed: {:?}", result);
 
         // Assert folder and symlink are gone
@@ -0,0 +1,401 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+use crate::{
+    addon_disk::{self, DiskAddonRepository},
+    addon_store::{self, AddOnsFolderUserMeta},
+    deploy::{self, LinkOptions},
+    error::AddonError,
+    game_process,
+    lock::OperationLock,
+    symlink::{self, LinkStyle},
+};
+
+#[derive(Debug, Serialize, Clone, Copy, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkChangeKind {
+    /// A missing link was created
+    Created,
+    /// A link pointing to the wrong or a stale target was replaced
+    Retargeted,
+    /// The link could not be repaired, see `reason`
+    Skipped,
+}
+
+/// A change made by `repair_folder` to a single link
+#[derive(Debug, Serialize, Clone, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkChange {
+    pub kind: LinkChangeKind,
+    pub repo_name: String,
+    /// Link path in the AddOns folder
+    pub link: String,
+    pub target: String,
+    pub reason: Option<String>,
+}
+
+fn display(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// Repositories in `.addonmanager` with the directory they were found in
+fn disk_repositories(addons_dir: &Path) -> Vec<(PathBuf, DiskAddonRepository)> {
+    let Ok(entries) = fs::read_dir(addons_dir.join(".addonmanager")) else {
+        return Vec::new();
+    };
+    let mut repos: Vec<(PathBuf, DiskAddonRepository)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_dir()
+                && !path
+                    .file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        })
+        .filter_map(|repo_dir| {
+            let repo = if repo_dir.join(".git").is_dir() {
+                addon_disk::create_disk_addon_repository_disk_only(&repo_dir)
+            } else {
+                addon_disk::create_non_git_addon_repository(&repo_dir)
+            };
+            match repo {
+                Ok(repo) => Some((repo_dir, repo)),
+                Err(e) => {
+                    eprintln!("Skipping {} during repair: {e}", repo_dir.display());
+                    None
+                }
+            }
+        })
+        .collect();
+    repos.sort_by(|a, b| a.0.cmp(&b.0));
+    repos
+}
+
+/// Recreate the links of every repository in `.addonmanager` according to the
+/// persisted enabled state. Missing links are only created for sub-addons saved as
+/// enabled; the ones without saved state may have been unlinked on purpose and are
+/// reported as skipped. Links pointing anywhere else than their sub-addon are
+/// replaced, e.g. after copying the game to another machine; real directories in
+/// the way are left alone.
+pub fn repair_folder(
+    addons_dir: &Path,
+    meta: Option<&AddOnsFolderUserMeta>,
+    options: LinkOptions,
+) -> Result<Vec<LinkChange>, AddonError> {
+    game_process::ensure_not_running(addons_dir, "repairing links", &mut |_| {})?;
+    let _lock = OperationLock::acquire_folder(addons_dir, "repair")?;
+    let mut changes = Vec::new();
+
+    for (repo_dir, repo) in disk_repositories(addons_dir) {
+        let repo_meta = meta.and_then(|m| m.repos.iter().find(|r| r.repo_url == repo.get_key()));
+        let repo_name = repo_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        for addon in &repo.addons {
+            let addon_meta = repo_meta.and_then(|r| r.addons.get(&addon.name));
+            let enabled = addon_meta.map(|m| m.enabled);
+            if enabled == Some(false) {
+                continue;
+            }
+            let link_name = addon_meta
+                .and_then(|m| m.name.clone())
+                .unwrap_or_else(|| addon.name.clone());
+            let link = addons_dir.join(&link_name);
+            let target = if addon.dir == "." {
+                repo_dir.clone()
+            } else {
+                repo_dir.join(&addon.dir)
+            };
+            let change = |kind, reason: Option<String>| LinkChange {
+                kind,
+                repo_name: repo_name.clone(),
+                link: display(&link),
+                target: display(&target),
+                reason,
+            };
+
+            // Copies are re-synced instead of relinked
+            if let Some(manifest) = deploy::read_manifest(addons_dir, &link_name) {
+                if let Err(e) = deploy::sync_deployment(addons_dir, &manifest) {
+                    changes.push(change(LinkChangeKind::Skipped, Some(e.to_string())));
+                }
+                continue;
+            }
+
+            let intact = symlink::is_addon_symlinked(&link)
+                && symlink::resolve_link_target(&link).as_deref() == Some(target.as_path());
+            if intact {
+                continue;
+            }
+            if enabled.is_none() && !symlink::is_addon_symlinked(&link) {
+                changes.push(change(
+                    LinkChangeKind::Skipped,
+                    Some("No saved enabled state, enable the addon to link it".to_string()),
+                ));
+                continue;
+            }
+
+            let kind = if symlink::is_addon_symlinked(&link) {
+                if let Err(e) = fs::remove_file(&link).or_else(|_| fs::remove_dir(&link)) {
+                    changes.push(change(
+                        LinkChangeKind::Skipped,
+                        Some(format!("Failed to remove wrong link: {e}")),
+                    ));
+                    continue;
+                }
+                LinkChangeKind::Retargeted
+            } else if link.exists() {
+                changes.push(change(
+                    LinkChangeKind::Skipped,
+                    Some("A file or directory that is not a link is in the way".to_string()),
+                ));
+                continue;
+            } else {
+                LinkChangeKind::Created
+            };
+
+            match deploy::link_addon(addons_dir, &target, &link, options) {
+                Ok(()) => changes.push(change(kind, None)),
+                Err(e) => changes.push(change(
+                    LinkChangeKind::Skipped,
+                    Some(format!("Failed to create link: {e}")),
+                )),
+            }
+        }
+    }
+    changes.sort_by(|a, b| a.link.cmp(&b.link));
+    Ok(changes)
+}
+
+/// Rewrite every link into `.addonmanager` in the given style, e.g. to turn
+/// absolute links into relative ones. Broken links are left for cleanup.
+pub fn migrate_links(addons_dir: &Path, style: LinkStyle) -> Result<Vec<LinkChange>, AddonError> {
+    let _lock = OperationLock::acquire_folder(addons_dir, "migrate")?;
+    let manager_dir = addons_dir.join(".addonmanager");
+    let mut changes = Vec::new();
+
+    let Ok(entries) = fs::read_dir(addons_dir) else {
+        return Ok(changes);
+    };
+    for link in entries.flatten().map(|entry| entry.path()) {
+        if !symlink::is_addon_symlinked(&link) || symlink::is_broken_link(&link) {
+            continue;
+        }
+        let (Some(target), Ok(current)) =
+            (symlink::resolve_link_target(&link), fs::read_link(&link))
+        else {
+            continue;
+        };
+        let Ok(relative) = target.strip_prefix(&manager_dir) else {
+            continue;
+        };
+        if symlink::link_target(&target, &link, style) == current {
+            continue;
+        }
+        let change = |kind, reason: Option<String>| LinkChange {
+            kind,
+            repo_name: relative
+                .components()
+                .next()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .unwrap_or_default(),
+            link: display(&link),
+            target: display(&target),
+            reason,
+        };
+        let result = fs::remove_file(&link)
+            .or_else(|_| fs::remove_dir(&link))
+            .and_then(|_| symlink::create_addon_link(&target, &link, style));
+        changes.push(match result {
+            Ok(()) => change(LinkChangeKind::Retargeted, None),
+            Err(e) => change(
+                LinkChangeKind::Skipped,
+                Some(format!("Failed to convert link: {e}")),
+            ),
+        });
+    }
+    changes.sort_by(|a, b| a.link.cmp(&b.link));
+    Ok(changes)
+}
+
+#[tauri::command]
+pub async fn repair_folder_cmd(
+    app_handle: AppHandle,
+    path: String,
+) -> Result<Vec<LinkChange>, String> {
+    let config = addon_store::load_user_config(&app_handle)?;
+    let options = deploy::configured_link_options(&app_handle, &path);
+    let changes = tauri::async_runtime::spawn_blocking(move || {
+        let meta = config.folders.iter().find(|f| f.path == path);
+        repair_folder(Path::new(&path), meta, options)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))??;
+
+    app_handle
+        .emit("addon-data-updated", ())
+        .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))?;
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addon_store::{AddonRepositoryUserMeta, AddonUserMeta, FileConfigStore};
+    use crate::install;
+    use crate::operation_reporter::OperationKey;
+    use crate::test_utils::{print_dir_tree, setup_addons_dir};
+    use crate::validate;
+
+    #[test]
+    fn test_repair_folder_relinks_from_meta() {
+        let (temp, addons_dir) = setup_addons_dir();
+        let repo_dir = validate::ensure_manager_dir(&addons_dir)
+            .unwrap()
+            .join("LocalRepo");
+        for addon in ["Missing", "Stale", "Disabled", "Unknown"] {
+            fs::create_dir_all(repo_dir.join(addon)).unwrap();
+            fs::write(repo_dir.join(addon).join(format!("{addon}.toc")), "").unwrap();
+        }
+        // A link left over from a game directory copied from another machine
+        let old_target = temp.path().join("old-machine/Stale");
+        symlink::create_symlink(&old_target, addons_dir.join("Stale")).unwrap();
+
+        let meta = AddOnsFolderUserMeta {
+            path: addons_dir.to_string_lossy().to_string(),
+            repos: vec![AddonRepositoryUserMeta {
+                repo_url: "local://LocalRepo".to_string(),
+                addons: [("Missing", true), ("Stale", true), ("Disabled", false)]
+                    .into_iter()
+                    .map(|(name, enabled)| {
+                        let meta = AddonUserMeta {
+                            enabled,
+                            name: None,
+                        };
+                        (name.to_string(), meta)
+                    })
+                    .collect(),
+                exclude_from_bulk_update: false,
+                auto_update: false,
+            }],
+            settings: Default::default(),
+        };
+
+        let changes = repair_folder(&addons_dir, Some(&meta), LinkOptions::default()).unwrap();
+        print_dir_tree(addons_dir.to_str().unwrap());
+
+        let kinds: Vec<(LinkChangeKind, &str)> = changes
+            .iter()
+            .map(|c| {
+                (
+                    c.kind,
+                    Path::new(&c.link).file_name().unwrap().to_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (LinkChangeKind::Created, "Missing"),
+                (LinkChangeKind::Retargeted, "Stale"),
+                (LinkChangeKind::Skipped, "Unknown")
+            ]
+        );
+        assert_eq!(
+            symlink::resolve_link_target(addons_dir.join("Stale")).unwrap(),
+            repo_dir.join("Stale")
+        );
+        assert!(!addons_dir.join("Disabled").exists());
+        assert!(!addons_dir.join("Unknown").exists());
+
+        let changes = repair_folder(&addons_dir, Some(&meta), LinkOptions::default()).unwrap();
+        assert!(
+            changes.iter().all(|c| c.kind == LinkChangeKind::Skipped),
+            "A second repair should change nothing: {changes:?}"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_repair_installed_addon_after_moving_game() {
+        let (temp, addons_dir) = setup_addons_dir();
+        let store = FileConfigStore::new(temp.path().join("config/addon-manager.json"));
+        let path = addons_dir.to_string_lossy().to_string();
+        addon_store::add_folder(&store, &path).unwrap();
+        let source = temp.path().join("MyAddon");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("MyAddon.toc"), "").unwrap();
+
+        let linked = install::install_local_folder(
+            source.to_string_lossy().to_string(),
+            path.clone(),
+            LinkOptions::default(),
+            |_| {},
+        )
+        .unwrap();
+        let key = OperationKey {
+            repo_url: "local://MyAddon".to_string(),
+            folder_path: path.clone(),
+        };
+        install::save_linked(&store, &key, &linked);
+
+        // The link still points into the game directory on the old machine
+        let link = addons_dir.join("MyAddon");
+        fs::remove_file(&link).unwrap();
+        symlink::create_symlink(temp.path().join("old-machine/MyAddon"), &link).unwrap();
+
+        let config = addon_store::load_user_config(&store).unwrap();
+        let repair = || repair_folder(&addons_dir, config.folders.first(), LinkOptions::default());
+        let changes = repair().unwrap();
+        print_dir_tree(addons_dir.to_str().unwrap());
+        assert_eq!(changes.len(), 1, "{changes:?}");
+        assert_eq!(changes[0].kind, LinkChangeKind::Retargeted);
+        let target = addons_dir.join(".addonmanager/MyAddon");
+        assert_eq!(symlink::resolve_link_target(&link).unwrap(), target);
+
+        // Linked by the install, so a missing link is recreated as well
+        fs::remove_file(&link).unwrap();
+        let changes = repair().unwrap();
+        assert_eq!(changes[0].kind, LinkChangeKind::Created);
+        assert!(repair().unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_migrate_absolute_links_to_relative() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let target = validate::ensure_manager_dir(&addons_dir)
+            .unwrap()
+            .join("LocalRepo/SubAddon");
+        fs::create_dir_all(&target).unwrap();
+        let link = addons_dir.join("SubAddon");
+        symlink::create_symlink(&target, &link).unwrap();
+
+        let changes = migrate_links(&addons_dir, LinkStyle::Relative).unwrap();
+        print_dir_tree(addons_dir.to_str().unwrap());
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, LinkChangeKind::Retargeted);
+        assert_eq!(
+            fs::read_link(&link).unwrap(),
+            PathBuf::from(".addonmanager/LocalRepo/SubAddon")
+        );
+        assert_eq!(symlink::resolve_link_target(&link).unwrap(), target);
+        assert!(
+            migrate_links(&addons_dir, LinkStyle::Relative)
+                .unwrap()
+                .is_empty(),
+            "Relative links should be left alone"
+        );
+    }
+}
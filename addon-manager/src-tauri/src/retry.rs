@@ -0,0 +1,267 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    time::{Duration, SystemTime},
+};
+
+/// Exponential backoff settings for transient git failures
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Short backoff for the fetches of a scan, which run one repository after another
+    pub fn scan() -> Self {
+        RetryPolicy {
+            max_attempts: 2,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+
+    /// A single attempt
+    pub fn once() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Backoff before retrying after `attempt` failed, with jitter in [delay/2, delay]
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+        let delay = exponential.min(self.max_delay);
+        delay.mul_f64(0.5 + jitter_fraction() / 2.0)
+    }
+}
+
+/// A transient failure about to be retried
+#[derive(Debug)]
+pub struct RetryAttempt<'a> {
+    /// The attempt that failed, starting at 1
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub delay: Duration,
+    pub error: &'a git2::Error,
+}
+
+impl RetryAttempt<'_> {
+    /// Message suitable for an `OperationEvent::Warning`
+    pub fn message(&self, action: &str) -> String {
+        format!(
+            "{action} failed (attempt {}/{}), retrying in {:.1}s: {}",
+            self.attempt,
+            self.max_attempts,
+            self.delay.as_secs_f64(),
+            self.error.message()
+        )
+    }
+}
+
+/// Whether a git2 error is likely to succeed on retry: network, timeout or 5xx failures.
+/// Authentication, missing repositories, certificates and cancellation are permanent.
+pub fn is_transient(err: &git2::Error) -> bool {
+    match err.code() {
+        git2::ErrorCode::Auth
+        | git2::ErrorCode::Certificate
+        | git2::ErrorCode::NotFound
+        | git2::ErrorCode::User
+        | git2::ErrorCode::Exists
+        | git2::ErrorCode::Locked => return false,
+        _ => {}
+    }
+
+    let message = err.message().to_lowercase();
+    if let Some(status) = http_status(&message) {
+        return status >= 500 || status == 408 || status == 429;
+    }
+    if [
+        "timed out",
+        "timeout",
+        "connection reset",
+        "connection refused",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle))
+    {
+        return true;
+    }
+    matches!(
+        err.class(),
+        git2::ErrorClass::Net | git2::ErrorClass::Http | git2::ErrorClass::Ssh
+    )
+}
+
+/// Whether the server answered with an HTTP status. Other network errors, such as
+/// failed name resolution or a refused connection, mean the remote was not reached.
+pub fn is_http_error(err: &git2::Error) -> bool {
+    http_status(&err.message().to_lowercase()).is_some()
+}
+
+/// Run `op`, retrying transient git errors with exponential backoff and jitter.
+/// `on_retry` is called before each wait; return `false` from it to give up.
+pub fn retry_transient<T, F, R>(
+    policy: &RetryPolicy,
+    mut op: F,
+    mut on_retry: R,
+) -> Result<T, git2::Error>
+where
+    F: FnMut() -> Result<T, git2::Error>,
+    R: FnMut(&RetryAttempt) -> bool,
+{
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < policy.max_attempts && is_transient(&e) => {
+                let retry = RetryAttempt {
+                    attempt,
+                    max_attempts: policy.max_attempts,
+                    delay: policy.delay_for(attempt),
+                    error: &e,
+                };
+                if !on_retry(&retry) {
+                    return Err(e);
+                }
+                std::thread::sleep(retry.delay);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Parse the status from libgit2 messages like "unexpected http status code: 502"
+fn http_status(message: &str) -> Option<u16> {
+    let (_, rest) = message.split_once("status code")?;
+    rest.trim_start_matches([':', ' '])
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Random value in [0, 1) without pulling in a rand dependency
+fn jitter_fraction() -> f64 {
+    let seed = RandomState::new().hash_one(SystemTime::now());
+    (seed % 10_000) as f64 / 10_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git_error(code: git2::ErrorCode, class: git2::ErrorClass, message: &str) -> git2::Error {
+        git2::Error::new(code, class, message)
+    }
+
+    #[test]
+    fn test_transient_error_classification() {
+        use git2::{ErrorClass, ErrorCode};
+
+        let transient = [
+            git_error(
+                ErrorCode::GenericError,
+                ErrorClass::Net,
+                "failed to connect",
+            ),
+            git_error(
+                ErrorCode::GenericError,
+                ErrorClass::Http,
+                "unexpected http status code: 502",
+            ),
+            git_error(
+                ErrorCode::GenericError,
+                ErrorClass::Os,
+                "operation timed out",
+            ),
+        ];
+        let permanent = [
+            git_error(ErrorCode::Auth, ErrorClass::Http, "authentication required"),
+            git_error(
+                ErrorCode::GenericError,
+                ErrorClass::Http,
+                "unexpected http status code: 404",
+            ),
+            git_error(ErrorCode::User, ErrorClass::Callback, "cancelled"),
+            git_error(ErrorCode::GenericError, ErrorClass::Reference, "bad ref"),
+        ];
+
+        for e in &transient {
+            assert!(is_transient(e), "Expected transient: {e}");
+        }
+        for e in &permanent {
+            assert!(!is_transient(e), "Expected permanent: {e}");
+        }
+    }
+
+    #[test]
+    fn test_retry_transient_retries_until_success() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(2),
+        };
+        let mut calls = 0;
+        let mut retries = Vec::new();
+        let result = retry_transient(
+            &policy,
+            || {
+                calls += 1;
+                if calls < 3 {
+                    Err(git2::Error::new(
+                        git2::ErrorCode::GenericError,
+                        git2::ErrorClass::Net,
+                        "connection reset by peer",
+                    ))
+                } else {
+                    Ok(calls)
+                }
+            },
+            |retry| {
+                retries.push(retry.message("Fetch"));
+                true
+            },
+        );
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(retries.len(), 2, "Expected 2 retries: {retries:?}");
+        assert!(retries[0].starts_with("Fetch failed (attempt 1/3)"));
+    }
+
+    #[test]
+    fn test_retry_transient_stops_on_permanent_error() {
+        let mut calls = 0;
+        let result: Result<(), _> = retry_transient(
+            &RetryPolicy::default(),
+            || {
+                calls += 1;
+                Err(git2::Error::new(
+                    git2::ErrorCode::NotFound,
+                    git2::ErrorClass::Reference,
+                    "reference not found",
+                ))
+            },
+            |_| true,
+        );
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1, "Permanent errors should not be retried");
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{addon_disk, error::AddonError, operation_reporter::OperationEvent};
+
+/// Directory in `.addonmanager` that archived saved variables are moved into
+const ARCHIVE_DIR: &str = ".archive";
+
+/// What to do with an addon's saved variables when it is deleted
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS, PartialEq, Eq, Default, ValueEnum)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum SavedVariablesAction {
+    #[default]
+    Keep,
+    Remove,
+    /// Move into `.addonmanager/.archive/<repo>-<timestamp>`
+    Archive,
+}
+
+/// Which kinds of saved variables a sub-addon declares in its TOC files
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct SavedVariablesDecl {
+    account: bool,
+    per_character: bool,
+}
+
+/// The `WTF` directory of the game installation the AddOns folder belongs to.
+/// The AddOns folder is expected at `<game>/Interface/AddOns`.
+pub fn wtf_dir(addons_dir: &Path) -> Option<PathBuf> {
+    let game_dir = addons_dir.parent()?.parent()?;
+    let wtf = game_dir.join("WTF");
+    wtf.is_dir().then_some(wtf)
+}
+
+/// Parse `## SavedVariables:` and `## SavedVariablesPerCharacter:` from all TOC files
+fn read_declarations(addon_dir: &Path) -> SavedVariablesDecl {
+    let mut decl = SavedVariablesDecl::default();
+    let Ok(entries) = fs::read_dir(addon_dir) else {
+        return decl;
+    };
+    for toc in entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension() == Some(OsStr::new("toc")))
+    {
+        let Ok(content) = fs::read_to_string(&toc) else {
+            continue;
+        };
+        for line in content.lines() {
+            let Some((key, value)) = line
+                .strip_prefix("##")
+                .and_then(|rest| rest.split_once(':'))
+            else {
+                continue;
+            };
+            if value.trim().is_empty() {
+                continue;
+            }
+            let key = key.trim();
+            if key.eq_ignore_ascii_case("SavedVariables") {
+                decl.account = true;
+            } else if key.eq_ignore_ascii_case("SavedVariablesPerCharacter") {
+                decl.per_character = true;
+            }
+        }
+    }
+    decl
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `WTF/Account/<account>` directories
+pub(crate) fn account_dirs(wtf: &Path) -> Vec<PathBuf> {
+    subdirs(&wtf.join("Account"))
+}
+
+/// `WTF/Account/<account>/<realm>/<character>` directories of an account
+pub(crate) fn character_dirs(account_dir: &Path) -> Vec<PathBuf> {
+    subdirs(account_dir)
+        .into_iter()
+        .filter(|realm| realm.file_name() != Some(OsStr::new("SavedVariables")))
+        .flat_map(|realm| subdirs(&realm))
+        .collect()
+}
+
+/// Every `SavedVariables` directory in `WTF`, account wide and per character
+pub(crate) fn saved_variables_dirs(wtf: &Path) -> Vec<PathBuf> {
+    account_dirs(wtf)
+        .into_iter()
+        .flat_map(|account| {
+            std::iter::once(account.join("SavedVariables"))
+                .chain(
+                    character_dirs(&account)
+                        .into_iter()
+                        .map(|c| c.join("SavedVariables")),
+                )
+                .collect::<Vec<_>>()
+        })
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// `<addon>.lua` and the game's `<addon>.lua.bak` backup in a SavedVariables directory
+fn existing_files(saved_variables_dir: &Path, addon_name: &str) -> Vec<PathBuf> {
+    [format!("{addon_name}.lua"), format!("{addon_name}.lua.bak")]
+        .into_iter()
+        .map(|file| saved_variables_dir.join(file))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Saved variable files in `WTF` belonging to a repository's sub-addons,
+/// based on what each sub-addon declares in its TOC files
+pub fn find_repo_saved_variables(addons_dir: &Path, repo_root: &Path) -> Vec<PathBuf> {
+    let Some(wtf) = wtf_dir(addons_dir) else {
+        return Vec::new();
+    };
+    let Ok(addons) = addon_disk::find_all_sub_addons(&repo_root.to_path_buf()) else {
+        return Vec::new();
+    };
+
+    let mut files = Vec::new();
+    for addon in addons {
+        let addon_dir = if addon.dir == "." {
+            repo_root.to_path_buf()
+        } else {
+            repo_root.join(&addon.dir)
+        };
+        let decl = read_declarations(&addon_dir);
+        for account in account_dirs(&wtf) {
+            if decl.account {
+                files.extend(existing_files(&account.join("SavedVariables"), &addon.name));
+            }
+            if decl.per_character {
+                for character in character_dirs(&account) {
+                    files.extend(existing_files(
+                        &character.join("SavedVariables"),
+                        &addon.name,
+                    ));
+                }
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Remove or archive saved variable files, reporting each one.
+/// Failures are reported as errors without aborting the rest.
+pub fn dispose_saved_variables<F>(
+    addons_dir: &Path,
+    repo_name: &str,
+    files: &[PathBuf],
+    action: SavedVariablesAction,
+    mut reporter: F,
+) where
+    F: FnMut(OperationEvent),
+{
+    if files.is_empty() || action == SavedVariablesAction::Keep {
+        return;
+    }
+    let Some(wtf) = wtf_dir(addons_dir) else {
+        return;
+    };
+    let archive = addons_dir
+        .join(".addonmanager")
+        .join(ARCHIVE_DIR)
+        .join(format!(
+            "{repo_name}-{}",
+            Utc::now().format("%Y%m%dT%H%M%SZ")
+        ));
+
+    for file in files {
+        let result = match action {
+            SavedVariablesAction::Keep => continue,
+            SavedVariablesAction::Remove => fs::remove_file(file)
+                .map(|_| format!("Removed saved variables: {}", file.display()))
+                .map_err(|e| AddonError::io("Failed to remove saved variables", &e)),
+            SavedVariablesAction::Archive => {
+                let relative = file.strip_prefix(&wtf).unwrap_or(file);
+                let destination = archive.join(relative);
+                destination
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::rename(file, &destination))
+                    .map(|_| {
+                        format!(
+                            "Archived saved variables: {} -> {}",
+                            file.display(),
+                            destination.display()
+                        )
+                    })
+                    .map_err(|e| AddonError::io("Failed to archive saved variables", &e))
+            }
+        };
+        match result {
+            Ok(message) => reporter(OperationEvent::Status(message)),
+            Err(e) => reporter(OperationEvent::Error(e.with_path(file))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{print_dir_tree, setup_addons_dir};
+    use crate::validate;
+
+    /// Creates a repo with one sub-addon and matching WTF files, returns (repo_root, wtf)
+    fn setup_repo_with_wtf(addons_dir: &Path) -> (PathBuf, PathBuf) {
+        let repo_root = validate::ensure_manager_dir(addons_dir)
+            .unwrap()
+            .join("FakeRepo");
+        let addon_dir = repo_root.join("FakeAddon");
+        fs::create_dir_all(&addon_dir).unwrap();
+        fs::write(
+            addon_dir.join("FakeAddon.toc"),
+            "## Interface: 30300\n## SavedVariables: FakeAddonDB\n## SavedVariablesPerCharacter: FakeAddonCharDB\n",
+        )
+        .unwrap();
+
+        let wtf = addons_dir.parent().unwrap().parent().unwrap().join("WTF");
+        let account = wtf.join("Account").join("ACCOUNT");
+        let character = account.join("Realm").join("Char");
+        for dir in [
+            account.join("SavedVariables"),
+            character.join("SavedVariables"),
+        ] {
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("FakeAddon.lua"), "FakeAddonDB = {}").unwrap();
+            fs::write(dir.join("OtherAddon.lua"), "OtherDB = {}").unwrap();
+        }
+        fs::write(account.join("SavedVariables").join("FakeAddon.lua.bak"), "").unwrap();
+        (repo_root, wtf)
+    }
+
+    #[test]
+    fn test_find_repo_saved_variables() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let (repo_root, wtf) = setup_repo_with_wtf(&addons_dir);
+        print_dir_tree(wtf.to_str().unwrap());
+
+        let files = find_repo_saved_variables(&addons_dir, &repo_root);
+        let account_sv = wtf.join("Account/ACCOUNT/SavedVariables");
+        assert_eq!(
+            files,
+            vec![
+                wtf.join("Account/ACCOUNT/Realm/Char/SavedVariables/FakeAddon.lua"),
+                account_sv.join("FakeAddon.lua"),
+                account_sv.join("FakeAddon.lua.bak"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_archive_saved_variables() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let (repo_root, wtf) = setup_repo_with_wtf(&addons_dir);
+        let files = find_repo_saved_variables(&addons_dir, &repo_root);
+
+        let mut events = Vec::new();
+        dispose_saved_variables(
+            &addons_dir,
+            "FakeRepo",
+            &files,
+            SavedVariablesAction::Archive,
+            |event| events.push(event),
+        );
+        print_dir_tree(
+            addons_dir
+                .parent()
+                .unwrap()
+                .parent()
+                .unwrap()
+                .to_str()
+                .unwrap(),
+        );
+
+        assert_eq!(events.len(), files.len(), "Expected one event per file");
+        assert!(files.iter().all(|f| !f.exists()), "Files were not moved");
+        assert!(
+            wtf.join("Account/ACCOUNT/SavedVariables/OtherAddon.lua")
+                .exists(),
+            "Unrelated saved variables must be kept"
+        );
+        let archive = addons_dir.join(".addonmanager").join(ARCHIVE_DIR);
+        assert_eq!(subdirs(&archive).len(), 1, "Expected one archive snapshot");
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::{
+    addon_discovery::AppState,
+    addon_disk::DiskAddOnsFolder,
+    addon_store::{self, AddOnsUserConfig},
+    backup,
+    operation_registry::CancellationToken,
+    operation_reporter::{
+        EventSink, OperationEvent, OperationKey, OperationType, emit_operation_event,
+    },
+    update::{self, OutdatedRepo, UpdateFilter},
+};
+
+/// Event carrying the `UpdateCheckSummary` of every scheduled check
+pub const SUMMARY_EVENT: &str = "update-check-summary";
+/// How often the scheduler wakes up to pick up a changed interval
+const TICK: Duration = Duration::from_secs(60);
+
+/// Result of a scheduled update check
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCheckSummary {
+    #[ts(type = "string")]
+    pub checked_at: DateTime<Utc>,
+    /// Outdated repositories left for the user to update
+    pub outdated: Vec<OutdatedRepo>,
+    /// Repositories flagged auto-update that were updated
+    pub updated: Vec<OperationKey>,
+    /// Repositories flagged auto-update whose update failed
+    pub failed: Vec<OperationKey>,
+    /// Folders whose game client was running; they were not checked
+    pub skipped_folders: Vec<String>,
+}
+
+/// Fetch every configured folder except the ones whose game is running and update
+/// the outdated repositories flagged auto-update. Returns the scanned folders with
+/// the summary.
+pub fn check_and_update(
+    config: &AddOnsUserConfig,
+    events: &impl EventSink,
+    is_game_running: impl Fn(&Path) -> bool,
+    backup_count: u32,
+) -> (Vec<DiskAddOnsFolder>, UpdateCheckSummary) {
+    let mut summary = UpdateCheckSummary {
+        checked_at: Utc::now(),
+        outdated: Vec::new(),
+        updated: Vec::new(),
+        failed: Vec::new(),
+        skipped_folders: Vec::new(),
+    };
+    let mut folders = Vec::new();
+    for folder in &config.folders {
+        if is_game_running(Path::new(&folder.path)) {
+            summary.skipped_folders.push(folder.path.clone());
+            continue;
+        }
+        let scanned =
+            DiskAddOnsFolder::scan_with_cancel(&folder.path, &CancellationToken::default(), events);
+        folders.push(scanned.unwrap_or_else(|e| {
+            eprintln!("Failed to scan path {:?}: {e:?}", folder.path);
+            DiskAddOnsFolder::default_with_error(&folder.path, e.to_string())
+        }));
+    }
+
+    for repo in update::outdated_repos(&folders, config, &UpdateFilter::default()) {
+        if !config.is_auto_update(&repo.folder_path, &repo.repo_url) {
+            summary.outdated.push(repo);
+            continue;
+        }
+        let key = OperationKey {
+            repo_url: repo.repo_url.clone(),
+            folder_path: repo.folder_path.clone(),
+        };
+        let emit = |event| {
+            if let Err(e) = emit_operation_event(events, &key, event) {
+                eprintln!("Failed to emit operation-event: {e}");
+            }
+        };
+        emit(OperationEvent::Started {
+            operation: OperationType::Update,
+        });
+        let result = update::update_addon_repo(
+            &repo.folder_path,
+            &repo.repo_url,
+            &repo.branch,
+            &CancellationToken::default(),
+            backup_count,
+            emit,
+        );
+        match result {
+            Ok(()) => {
+                emit(OperationEvent::Completed);
+                events.finish_operation(&key);
+                summary.updated.push(key);
+            }
+            Err(e) => {
+                emit(OperationEvent::Error(e));
+                events.finish_operation(&key);
+                summary.failed.push(key);
+                summary.outdated.push(repo);
+            }
+        }
+    }
+    (folders, summary)
+}
+
+fn run_scheduled_check(app: &AppHandle, config: &AddOnsUserConfig) {
+    let backup_count = backup::configured_backup_count(app);
+    let (folders, summary) = check_and_update(
+        config,
+        app,
+        crate::game_process::is_game_running,
+        backup_count,
+    );
+
+    // Keep the fetched refs for the next disk refresh of the UI
+    if let Some(state) = app.try_state::<AppState>() {
+        state.update_folders(folders);
+    }
+
+    println!(
+        "Scheduled update check: {} outdated, {} updated, {} failed, {} skipped",
+        summary.outdated.len(),
+        summary.updated.len(),
+        summary.failed.len(),
+        summary.skipped_folders.len()
+    );
+    if let Err(e) = app.emit(SUMMARY_EVENT, summary) {
+        eprintln!("Failed to emit {SUMMARY_EVENT}: {e}");
+    }
+    if let Err(e) = app.emit("addon-data-updated", ()) {
+        eprintln!("Failed to emit addon-data-updated: {e}");
+    }
+}
+
+/// Run update checks in the background at the interval configured in
+/// `update_check_interval_minutes`. The config is re-read on every tick so
+/// changing the interval does not need a restart.
+pub fn start(app: AppHandle) {
+    thread::spawn(move || {
+        let mut last_check: Option<Instant> = None;
+        loop {
+            thread::sleep(TICK);
+            let Ok(config) = addon_store::load_user_config(&app) else {
+                continue;
+            };
+            let Some(interval) = config
+                .update_check_interval_minutes
+                .filter(|minutes| *minutes > 0)
+            else {
+                continue;
+            };
+            let interval = Duration::from_secs(u64::from(interval) * 60);
+            if last_check.is_some_and(|at| at.elapsed() < interval) {
+                continue;
+            }
+            last_check = Some(Instant::now());
+            run_scheduled_check(&app, &config);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Mutex};
+
+    use super::*;
+    use crate::addon_store::AddOnsFolderUserMeta;
+    use crate::operation_reporter::OperationEventPayload;
+    use crate::test_utils::setup_addons_dir;
+    use crate::validate;
+
+    #[derive(Default)]
+    struct RecordedEvents(Mutex<Vec<OperationEventPayload>>);
+
+    impl EventSink for RecordedEvents {
+        fn operation_event(&self, payload: OperationEventPayload) -> Result<(), String> {
+            self.0.lock().unwrap().push(payload);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_check_skips_folders_with_running_game() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let repo_dir = validate::ensure_manager_dir(&addons_dir)
+            .unwrap()
+            .join("LocalRepo");
+        fs::create_dir_all(&repo_dir).unwrap();
+        fs::write(repo_dir.join("LocalRepo.toc"), "").unwrap();
+
+        let path = addons_dir.to_string_lossy().to_string();
+        let config = AddOnsUserConfig {
+            folders: vec![AddOnsFolderUserMeta {
+                path: path.clone(),
+                repos: Vec::new(),
+                settings: Default::default(),
+            }],
+            ..Default::default()
+        };
+        let events = RecordedEvents::default();
+
+        let (folders, summary) = check_and_update(&config, &events, |_| true, 0);
+        assert!(folders.is_empty());
+        assert_eq!(summary.skipped_folders, vec![path.clone()]);
+
+        let (folders, summary) = check_and_update(&config, &events, |_| false, 0);
+        assert_eq!(folders.len(), 1);
+        assert_eq!(folders[0].repositories.len(), 1);
+        assert!(summary.skipped_folders.is_empty());
+        assert!(summary.outdated.is_empty() && summary.updated.is_empty());
+        assert!(events.0.lock().unwrap().is_empty());
+    }
+}
@@ -1,4 +1,19 @@
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// How links in the AddOns folder refer to their target
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS, Default, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkStyle {
+    #[default]
+    Absolute,
+    /// Relative to the AddOns folder, e.g. `.addonmanager/<repo>/<dir>`, so the
+    /// game folder can be moved. Unix only, junctions on Windows are always absolute.
+    Relative,
+}
 
 #[cfg(target_family = "unix")]
 pub fn create_symlink<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> std::io::Result<()> {
@@ -10,15 +25,61 @@ pub fn create_symlink<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> std::io
     junction::create(src.as_ref(), dst.as_ref())
 }
 
-/// Check if a path is a symlink (Unix) or junction (Windows)
+/// Check if a path is a symlink (Unix) or junction (Windows).
+/// Broken links count as linked; see `is_broken_link`.
 pub fn is_addon_symlinked<P: AsRef<Path>>(path: P) -> bool {
     let p = path.as_ref();
     #[cfg(target_family = "windows")]
     {
-        junction::exists(p).unwrap_or(false)
+        junction::exists(p).unwrap_or(false) || p.is_symlink()
     }
     #[cfg(not(target_family = "windows"))]
     {
-        p.exists() && p.is_symlink()
+        p.is_symlink()
+    }
+}
+
+/// The path to write into a link at `link` pointing to `target`.
+/// Falls back to `target` when no relative path within the link's folder exists.
+pub fn link_target(target: &Path, link: &Path, style: LinkStyle) -> PathBuf {
+    if cfg!(target_family = "unix")
+        && style == LinkStyle::Relative
+        && let Some(relative) = link.parent().and_then(|dir| target.strip_prefix(dir).ok())
+    {
+        return relative.to_path_buf();
+    }
+    target.to_path_buf()
+}
+
+/// Create a link to an addon directory in the given style
+pub fn create_addon_link(target: &Path, link: &Path, style: LinkStyle) -> std::io::Result<()> {
+    create_symlink(link_target(target, link, style), link)
+}
+
+/// A symlink or junction whose target no longer exists
+pub fn is_broken_link<P: AsRef<Path>>(path: P) -> bool {
+    let p = path.as_ref();
+    is_addon_symlinked(p) && !p.exists()
+}
+
+/// Absolute target of a link; relative targets are resolved against the link's
+/// directory and `.`/`..` are removed without touching the filesystem
+pub fn resolve_link_target<P: AsRef<Path>>(link: P) -> Option<PathBuf> {
+    let link = link.as_ref();
+    let target = std::fs::read_link(link).ok()?;
+    let joined = match link.parent() {
+        Some(parent) if target.is_relative() => parent.join(target),
+        _ => target,
+    };
+    let mut resolved = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            other => resolved.push(other),
+        }
     }
+    Some(resolved)
 }
@@ -0,0 +1,379 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+use crate::{
+    addon_store::{self, ConfigStore},
+    deploy::{self, LinkOptions},
+    error::{AddonError, AddonErrorKind},
+    game_process,
+    lock::OperationLock,
+    operation_history::finish_operation,
+    operation_reporter::*,
+    validate,
+};
+
+/// Quarantine directory inside `.addonmanager` that deleted repositories are moved into
+const TRASH_DIR: &str = ".trash";
+/// Days a deleted repository is kept before it is purged, unless configured otherwise
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 7;
+
+/// A symlink in the AddOns folder that pointed into a deleted repository
+#[derive(Debug, Serialize, Deserialize, Clone, TS, PartialEq, Eq)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedLink {
+    /// Symlink name in the AddOns folder
+    pub name: String,
+    /// Link target relative to the repository root
+    pub target: String,
+}
+
+/// A deleted repository kept in `.addonmanager/.trash/<id>`
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    /// `<repo>-<timestamp>`, the directory name in the trash
+    pub id: String,
+    pub repo_name: String,
+    pub repo_url: String,
+    #[ts(type = "string")]
+    pub deleted_at: DateTime<Utc>,
+    pub links: Vec<TrashedLink>,
+}
+
+pub fn trash_dir(addons_dir: &Path) -> PathBuf {
+    addons_dir.join(".addonmanager").join(TRASH_DIR)
+}
+
+fn metadata_path(trash: &Path, id: &str) -> PathBuf {
+    trash.join(format!("{id}.json"))
+}
+
+/// Move a repository from `.addonmanager` into the trash, remembering its symlinks
+pub fn move_to_trash(
+    addons_dir: &Path,
+    repo_name: &str,
+    repo_url: &str,
+    links: Vec<TrashedLink>,
+) -> Result<TrashEntry, AddonError> {
+    let manager_dir = validate::ensure_manager_dir(addons_dir)?;
+    let repo_dir = manager_dir.join(repo_name);
+    let trash = trash_dir(addons_dir);
+    fs::create_dir_all(&trash)
+        .map_err(|e| AddonError::io("Failed to create trash dir", &e).with_path(&trash))?;
+
+    let deleted_at = Utc::now();
+    let base_id = format!("{repo_name}-{}", deleted_at.format("%Y%m%dT%H%M%SZ"));
+    let mut id = base_id.clone();
+    let mut suffix = 1;
+    while trash.join(&id).exists() || metadata_path(&trash, &id).exists() {
+        id = format!("{base_id}-{suffix}");
+        suffix += 1;
+    }
+
+    let entry = TrashEntry {
+        id,
+        repo_name: repo_name.to_string(),
+        repo_url: repo_url.to_string(),
+        deleted_at,
+        links,
+    };
+    let destination = trash.join(&entry.id);
+    fs::rename(&repo_dir, &destination).map_err(|e| {
+        AddonError::io("Failed to move repository to trash", &e).with_path(&repo_dir)
+    })?;
+    write_metadata(&trash, &entry)?;
+    Ok(entry)
+}
+
+fn write_metadata(trash: &Path, entry: &TrashEntry) -> Result<(), AddonError> {
+    let path = metadata_path(trash, &entry.id);
+    let content = serde_json::to_string_pretty(entry).map_err(|e| {
+        AddonError::new(AddonErrorKind::Other, "Failed to serialize trash entry").with_cause(e)
+    })?;
+    fs::write(&path, content)
+        .map_err(|e| AddonError::io("Failed to write trash metadata", &e).with_path(&path))
+}
+
+/// All deleted repositories in the AddOns folder's trash, newest first
+pub fn list_trash(addons_dir: &Path) -> Vec<TrashEntry> {
+    let trash = trash_dir(addons_dir);
+    let Ok(entries) = fs::read_dir(&trash) else {
+        return Vec::new();
+    };
+    let mut items: Vec<TrashEntry> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let content = fs::read_to_string(&path).ok()?;
+            match serde_json::from_str(&content) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    eprintln!("Skipping unreadable trash entry {}: {e}", path.display());
+                    None
+                }
+            }
+        })
+        .collect();
+    items.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    items
+}
+
+/// Move a trashed repository back into `.addonmanager` and recreate its symlinks.
+/// Links whose name is already taken in the AddOns folder are skipped with a warning.
+pub fn restore_from_trash<F>(
+    addons_dir: &Path,
+    id: &str,
+    options: LinkOptions,
+    mut reporter: F,
+) -> Result<TrashEntry, AddonError>
+where
+    F: FnMut(OperationEvent),
+{
+    let trash = trash_dir(addons_dir);
+    let entry = list_trash(addons_dir)
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| {
+            AddonError::new(AddonErrorKind::NotFound, format!("No deleted addon '{id}'"))
+                .with_path(&trash)
+        })?;
+
+    game_process::ensure_not_running(addons_dir, "restoring addons", &mut reporter)?;
+    let manager_dir = validate::ensure_manager_dir(addons_dir)?;
+    let _lock = OperationLock::acquire_repo(addons_dir, &entry.repo_name, "restore")?;
+    let repo_dir = manager_dir.join(&entry.repo_name);
+    if repo_dir.exists() {
+        return Err(AddonError::new(
+            AddonErrorKind::InvalidInput,
+            "A repository with the same name is already installed",
+        )
+        .with_path(&repo_dir));
+    }
+
+    reporter(OperationEvent::Status(format!(
+        "Restoring '{}' from trash...",
+        entry.repo_name
+    )));
+    let source = trash.join(&entry.id);
+    fs::rename(&source, &repo_dir).map_err(|e| {
+        AddonError::io("Failed to restore repository from trash", &e).with_path(&source)
+    })?;
+
+    let _folder_lock = OperationLock::acquire_folder(addons_dir, "link")?;
+    for link in &entry.links {
+        let link_path = addons_dir.join(&link.name);
+        if link_path.exists() || link_path.is_symlink() {
+            reporter(OperationEvent::Warning(format!(
+                "Skipped link '{}': a file with that name already exists",
+                link.name
+            )));
+            continue;
+        }
+        let target = if link.target == "." {
+            repo_dir.clone()
+        } else {
+            repo_dir.join(&link.target)
+        };
+        reporter(OperationEvent::Status(format!("Linking '{}'", link.name)));
+        if let Err(e) = deploy::link_addon(addons_dir, &target, &link_path, options) {
+            reporter(OperationEvent::Error(e));
+        }
+    }
+
+    let metadata = metadata_path(&trash, &entry.id);
+    if let Err(e) = fs::remove_file(&metadata) {
+        eprintln!(
+            "Failed to remove trash metadata {}: {e}",
+            metadata.display()
+        );
+    }
+    Ok(entry)
+}
+
+/// Permanently remove trash entries deleted longer ago than `retention_days`.
+/// Returns the purged entries.
+pub fn purge_expired(addons_dir: &Path, retention_days: u32) -> Vec<TrashEntry> {
+    let trash = trash_dir(addons_dir);
+    let cutoff = Utc::now() - chrono::Duration::days(i64::from(retention_days));
+    let mut purged = Vec::new();
+    for entry in list_trash(addons_dir) {
+        if entry.deleted_at > cutoff {
+            continue;
+        }
+        let dir = trash.join(&entry.id);
+        if dir.exists()
+            && let Err(e) = fs::remove_dir_all(&dir)
+        {
+            eprintln!("Failed to purge {}: {e}", dir.display());
+            continue;
+        }
+        let metadata = metadata_path(&trash, &entry.id);
+        if let Err(e) = fs::remove_file(&metadata) {
+            eprintln!(
+                "Failed to remove trash metadata {}: {e}",
+                metadata.display()
+            );
+        }
+        purged.push(entry);
+    }
+    purged
+}
+
+/// Purge expired trash entries using the retention configured by the user
+pub fn purge_expired_with_config(store: &impl ConfigStore, addons_dir: &Path) -> Vec<TrashEntry> {
+    let retention_days = addon_store::load_user_config(store)
+        .ok()
+        .and_then(|config| config.trash_retention_days)
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS);
+    purge_expired(addons_dir, retention_days)
+}
+
+#[tauri::command]
+pub async fn list_deleted_addons_cmd(
+    app_handle: AppHandle,
+    path: String,
+) -> Result<Vec<TrashEntry>, String> {
+    let addons_dir = PathBuf::from(&path);
+    purge_expired_with_config(&app_handle, &addons_dir);
+    Ok(list_trash(&addons_dir))
+}
+
+#[tauri::command]
+pub async fn restore_deleted_addon_cmd(
+    app_handle: AppHandle,
+    path: String,
+    trash_id: String,
+) -> Result<(), String> {
+    let addons_dir = PathBuf::from(&path);
+    let repo_url = list_trash(&addons_dir)
+        .into_iter()
+        .find(|entry| entry.id == trash_id)
+        .map(|entry| entry.repo_url)
+        .unwrap_or_default();
+    let operation_key = OperationKey {
+        repo_url,
+        folder_path: path,
+    };
+
+    emit_operation_event(
+        &app_handle,
+        &operation_key,
+        OperationEvent::Started {
+            operation: OperationType::Install,
+        },
+    )
+    .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+
+    let task_handle = app_handle.clone();
+    let task_key = operation_key.clone();
+    let options = deploy::configured_link_options(&app_handle, &operation_key.folder_path);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        restore_from_trash(&addons_dir, &trash_id, options, |event| {
+            if let Err(e) = emit_operation_event(&task_handle, &task_key, event) {
+                eprintln!("Failed to emit operation-event: {e}");
+            }
+        })
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?;
+
+    let completion_event = match &result {
+        Ok(_) => OperationEvent::Completed,
+        Err(e) => OperationEvent::Error(e.clone()),
+    };
+    if let Err(e) = emit_operation_event(&app_handle, &operation_key, completion_event) {
+        eprintln!("Failed to emit operation-event: {e}");
+    }
+
+    finish_operation(&app_handle, &operation_key);
+
+    app_handle
+        .emit("addon-data-updated", ())
+        .map_err(|e| format!("Failed to emit addon-data-updated: {e}"))?;
+
+    result.map(|_| ()).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{print_dir_tree, setup_addons_dir};
+
+    fn setup_repo(addons_dir: &Path, repo_name: &str) -> PathBuf {
+        let manager_dir = validate::ensure_manager_dir(addons_dir).unwrap();
+        let repo_dir = manager_dir.join(repo_name);
+        fs::create_dir_all(repo_dir.join("SubAddon")).unwrap();
+        fs::write(
+            repo_dir.join("SubAddon").join("SubAddon.toc"),
+            "## Title: Sub",
+        )
+        .unwrap();
+        repo_dir
+    }
+
+    #[test]
+    fn test_move_to_trash_and_restore() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let repo_dir = setup_repo(&addons_dir, "FakeRepo");
+        let links = vec![TrashedLink {
+            name: "SubAddon".to_string(),
+            target: "SubAddon".to_string(),
+        }];
+
+        let entry = move_to_trash(&addons_dir, "FakeRepo", "local://FakeRepo", links).unwrap();
+        print_dir_tree(addons_dir.to_str().unwrap());
+        assert!(!repo_dir.exists(), "Repo was not moved out");
+        assert!(trash_dir(&addons_dir).join(&entry.id).is_dir());
+        assert_eq!(list_trash(&addons_dir).len(), 1);
+
+        let mut warnings = Vec::new();
+        restore_from_trash(&addons_dir, &entry.id, LinkOptions::default(), |event| {
+            if let OperationEvent::Warning(w) = event {
+                warnings.push(w);
+            }
+        })
+        .unwrap();
+        print_dir_tree(addons_dir.to_str().unwrap());
+
+        assert!(repo_dir.join("SubAddon").is_dir(), "Repo was not restored");
+        let link = addons_dir.join("SubAddon");
+        assert!(link.is_symlink(), "Symlink was not recreated");
+        assert_eq!(fs::read_link(&link).unwrap(), repo_dir.join("SubAddon"));
+        assert!(warnings.is_empty(), "Unexpected warnings: {warnings:?}");
+        assert!(
+            list_trash(&addons_dir).is_empty(),
+            "Trash entry was not removed"
+        );
+    }
+
+    #[test]
+    fn test_purge_expired_keeps_recent_entries() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        setup_repo(&addons_dir, "Old");
+        setup_repo(&addons_dir, "New");
+
+        let mut old = move_to_trash(&addons_dir, "Old", "local://Old", Vec::new()).unwrap();
+        old.deleted_at = Utc::now() - chrono::Duration::days(30);
+        write_metadata(&trash_dir(&addons_dir), &old).unwrap();
+        let new = move_to_trash(&addons_dir, "New", "local://New", Vec::new()).unwrap();
+
+        let purged = purge_expired(&addons_dir, DEFAULT_TRASH_RETENTION_DAYS);
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].id, old.id);
+        assert!(!trash_dir(&addons_dir).join(&old.id).exists());
+
+        let remaining = list_trash(&addons_dir);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, new.id);
+    }
+}
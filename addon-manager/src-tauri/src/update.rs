@@ -1,65 +1,143 @@
-use git2::{FetchOptions, Repository, ResetType};
-use std::path::Path;
+use git2::{FetchOptions, Oid, RemoteCallbacks, Repository, ResetType};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path};
 use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
 
-use crate::{addon_discovery::AppState, git, operation_reporter::*, validate};
+use crate::{
+    addon_discovery::AppState,
+    addon_disk::{DiskAddOnsFolder, DiskAddonSource},
+    addon_store::{self, AddOnsUserConfig},
+    backup, deploy,
+    error::{AddonError, AddonErrorKind},
+    game_process, git,
+    lock::OperationLock,
+    operation_history::finish_operation,
+    operation_registry::{CancellationToken, OperationRegistry},
+    operation_reporter::*,
+    retry::{self, RetryPolicy},
+    validate,
+};
 
 /// Perform a forced update of the repository at the given path and branch.
 /// Fetches from origin, force resets local branch to remote HEAD.
-fn update_addon_repo(path: &str, url: &str, branch: &str) -> Result<(), String> {
+/// Transient fetch failures are retried and reported as warnings.
+/// Saved variables of the repo's sub-addons are backed up before the reset,
+/// keeping the newest `backup_count` snapshots.
+pub(crate) fn update_addon_repo<F>(
+    path: &str,
+    url: &str,
+    branch: &str,
+    cancel: &CancellationToken,
+    backup_count: u32,
+    mut reporter: F,
+) -> Result<(), AddonError>
+where
+    F: FnMut(OperationEvent),
+{
     let addons_dir = Path::new(path);
-    let manager_dir = validate::ensure_manager_dir(addons_dir)
-        .map_err(|e| format!("Failed to ensure manager dir: {e}"))?;
+    game_process::ensure_not_running(addons_dir, "updating", &mut reporter)?;
+    let manager_dir = validate::ensure_manager_dir(addons_dir)?;
 
-    let (_owner, repo_name) =
-        git::extract_owner_repo_from_url(url).map_err(|e| format!("Invalid repo URL: {e}"))?;
+    let (_owner, repo_name) = git::extract_owner_repo_from_url(url).map_err(|e| {
+        AddonError::new(AddonErrorKind::InvalidInput, "Invalid repo URL")
+            .with_url(url)
+            .with_cause(e)
+    })?;
+    let _lock = OperationLock::acquire_repo(addons_dir, &repo_name, "update")?;
     let repo_dir = manager_dir.join(&repo_name);
+    let git_err = |message: &str, e: git2::Error| {
+        AddonError::git(message, &e)
+            .with_url(url)
+            .with_path(&repo_dir)
+    };
 
-    let repo = Repository::open(&repo_dir)
-        .map_err(|e| format!("Failed to open repo {}: {e}", repo_dir.display()))?;
-
-    // Use HTTPS anonymous fetch for public repositories
-    let mut fo = FetchOptions::new();
+    let repo = Repository::open(&repo_dir).map_err(|e| git_err("Failed to open repo", e))?;
 
     let branch_name = branch.strip_prefix("origin/").unwrap_or(branch);
 
     let mut remote = repo
         .find_remote("origin")
-        .map_err(|e| format!("Failed to find remote: {e}"))?;
-    remote
-        .fetch(&[branch_name], Some(&mut fo), None)
-        .map_err(|e| format!("Fetch failed: {e}"))?;
+        .map_err(|e| git_err("Failed to find remote", e))?;
+    let fetch_result = retry::retry_transient(
+        &RetryPolicy::default(),
+        || {
+            // Use HTTPS anonymous fetch for public repositories
+            let mut callbacks = RemoteCallbacks::new();
+            callbacks.transfer_progress(|_| !cancel.is_cancelled());
+            let mut fo = FetchOptions::new();
+            fo.remote_callbacks(callbacks);
+            remote.fetch(&[branch_name], Some(&mut fo), None)
+        },
+        |retry| {
+            reporter(OperationEvent::Warning(retry.message("Fetch")));
+            !cancel.is_cancelled()
+        },
+    );
+    if let Err(e) = fetch_result {
+        if cancel.is_cancelled() {
+            return Err(
+                AddonError::new(AddonErrorKind::Cancelled, "Update cancelled").with_url(url),
+            );
+        }
+        return Err(git_err("Fetch failed", e));
+    }
 
     // Get the fetched commit
     let remote_ref = format!("refs/remotes/origin/{branch_name}");
     let commit = repo
         .find_reference(&remote_ref)
         .and_then(|r| r.peel_to_commit())
-        .map_err(|e| format!("Failed to get remote HEAD: {e}"))?;
+        .map_err(|e| git_err("Failed to get remote HEAD", e))?;
     let oid = commit.id();
 
+    // A failed backup should not block the update, but the user needs to know
+    match backup::backup_saved_variables(addons_dir, &repo_name, backup_count) {
+        Ok(Some(backup)) => reporter(OperationEvent::Status(format!(
+            "Backed up {} saved variable files",
+            backup.files.len()
+        ))),
+        Ok(None) => {}
+        Err(e) => reporter(OperationEvent::Warning(format!(
+            "Failed to back up saved variables: {e}"
+        ))),
+    }
+
     let local_ref = format!("refs/heads/{branch_name}");
     repo.reference(&local_ref, oid, true, "force update")
-        .map_err(|e| format!("Failed to update branch ref: {e}"))?;
+        .map_err(|e| git_err("Failed to update branch ref", e))?;
 
     // Checkout the branch so HEAD points to it
     let obj = repo
         .revparse_single(&local_ref)
-        .map_err(|e| format!("Failed to revparse branch for checkout: {e}"))?;
+        .map_err(|e| git_err("Failed to revparse branch for checkout", e))?;
     repo.checkout_tree(&obj, None)
-        .map_err(|e| format!("Failed to checkout tree: {e}"))?;
+        .map_err(|e| git_err("Failed to checkout tree", e))?;
     repo.set_head(&local_ref)
-        .map_err(|e| format!("Failed to set HEAD: {e}"))?;
+        .map_err(|e| git_err("Failed to set HEAD", e))?;
 
     // Hard reset working tree
     repo.reset(commit.as_object(), ResetType::Hard, None)
-        .map_err(|e| format!("Failed to reset repo: {e}"))?;
+        .map_err(|e| git_err("Failed to reset repo", e))?;
+
+    // Copies and hardlink trees do not follow the working tree on their own
+    match deploy::sync_repo_deployments(addons_dir, &repo_name) {
+        Ok(stats) if stats.placed + stats.removed > 0 => reporter(OperationEvent::Status(format!(
+            "Synced {} files, removed {}",
+            stats.placed, stats.removed
+        ))),
+        Ok(_) => {}
+        Err(e) => reporter(OperationEvent::Warning(format!(
+            "Failed to sync deployed files: {e}"
+        ))),
+    }
 
     Ok(())
 }
 
 async fn perform_update_op(
     app_handle: &AppHandle,
+    registry: &OperationRegistry,
     url: String,
     path: String,
     branch: String,
@@ -69,38 +147,43 @@ async fn perform_update_op(
         folder_path: path.clone(),
     };
 
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key.clone(),
-                event: OperationEvent::Started {
-                    operation: OperationType::Update,
-                },
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+    emit_operation_event(
+        app_handle,
+        &operation_key,
+        OperationEvent::Started {
+            operation: OperationType::Update,
+        },
+    )
+    .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
 
-    let result =
-        tauri::async_runtime::spawn_blocking(move || update_addon_repo(&path, &url, &branch))
-            .await
-            .map_err(|e| format!("Task join error: {e}"))?;
+    let cancel = registry.register(&operation_key);
+    let task_cancel = cancel.clone();
+    let task_handle = app_handle.clone();
+    let task_key = operation_key.clone();
+    let backup_count = backup::configured_backup_count(app_handle);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        update_addon_repo(&path, &url, &branch, &task_cancel, backup_count, |event| {
+            if let Err(e) = emit_operation_event(&task_handle, &task_key, event) {
+                eprintln!("Failed to emit operation-event: {e}");
+            }
+        })
+    })
+    .await;
+    registry.unregister(&operation_key, &cancel);
+    let result = result.map_err(|e| format!("Task join error: {e}"))?;
 
     let completion_event = match &result {
         Ok(_) => OperationEvent::Completed,
+        Err(_) if cancel.is_cancelled() => OperationEvent::Cancelled,
         Err(e) => OperationEvent::Error(e.clone()),
     };
-    app_handle
-        .emit(
-            "operation-event",
-            OperationEventPayload {
-                key: operation_key,
-                event: completion_event,
-            },
-        )
-        .map_err(|e| format!("Failed to emit operation-event: {e}"))?;
+    if let Err(e) = emit_operation_event(app_handle, &operation_key, completion_event) {
+        eprintln!("Failed to emit operation-event: {e}");
+    }
+
+    finish_operation(app_handle, &operation_key);
 
-    result
+    result.map_err(Into::into)
 }
 
 #[tauri::command]
@@ -109,8 +192,9 @@ pub async fn update_addon_cmd(
     url: String,
     path: String,
     branch: String,
+    registry: tauri::State<'_, OperationRegistry>,
 ) -> Result<(), String> {
-    let result = perform_update_op(&app_handle, url.clone(), path, branch).await;
+    let result = perform_update_op(&app_handle, &registry, url.clone(), path, branch).await;
     if let Ok(()) = result {
     } else if let Err(e) = result {
         eprintln!("Update failed for {url}: {e}");
@@ -123,39 +207,191 @@ pub async fn update_addon_cmd(
     Ok(())
 }
 
+/// Restricts which repositories "update all" touches. Empty include lists match
+/// everything; excludes win over includes.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, TS, clap::Args)]
+#[command(about = None, long_about = None)]
+#[ts(export)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UpdateFilter {
+    /// Only update repositories in these AddOns folders
+    #[arg(long = "folder")]
+    pub folders: Vec<String>,
+    /// Skip repositories in these AddOns folders
+    #[arg(long = "exclude-folder")]
+    pub exclude_folders: Vec<String>,
+    /// Only update repositories whose URL matches one of these globs, e.g. `*github.com/owner/*`
+    #[arg(long = "repo")]
+    pub repos: Vec<String>,
+    /// Skip repositories whose URL matches one of these globs
+    #[arg(long = "exclude-repo")]
+    pub exclude_repos: Vec<String>,
+    /// Only update repositories of these owners
+    #[arg(long = "owner")]
+    pub owners: Vec<String>,
+    /// Skip repositories of these owners
+    #[arg(long = "exclude-owner")]
+    pub exclude_owners: Vec<String>,
+}
+
+impl UpdateFilter {
+    pub fn includes_folder(&self, folder_path: &str) -> bool {
+        (self.folders.is_empty() || self.folders.iter().any(|f| f == folder_path))
+            && !self.exclude_folders.iter().any(|f| f == folder_path)
+    }
+
+    pub fn matches(&self, folder_path: &str, repo_url: &str, owner: &str) -> bool {
+        let url_matches = |globs: &[String]| globs.iter().any(|g| glob_matches(g, repo_url));
+        let owner_matches =
+            |owners: &[String]| owners.iter().any(|o| o.eq_ignore_ascii_case(owner));
+        self.includes_folder(folder_path)
+            && (self.repos.is_empty() || url_matches(&self.repos))
+            && (self.owners.is_empty() || owner_matches(&self.owners))
+            && !url_matches(&self.exclude_repos)
+            && !owner_matches(&self.exclude_owners)
+    }
+}
+
+/// Case insensitive match of `text` against a pattern with `*` and `?` wildcards
+pub(crate) fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, start)) => {
+                    backtrack = Some((star, start + 1));
+                    p = star + 1;
+                    t = start + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Git repositories on a branch whose local HEAD differs from the last fetched
+/// remote ref. Repositories excluded by the filter or marked as excluded from
+/// bulk updates in the config are left out. `behind` is not computed.
+fn outdated_git_repos<'a>(
+    folders: impl IntoIterator<Item = (&'a String, &'a DiskAddOnsFolder)> + 'a,
+    config: &'a AddOnsUserConfig,
+    filter: &'a UpdateFilter,
+) -> impl Iterator<Item = OutdatedRepo> + 'a {
+    folders
+        .into_iter()
+        .flat_map(move |(folder_path, disk_folder)| {
+            disk_folder.repositories.iter().filter_map(move |repo| {
+                if let DiskAddonSource::Git {
+                    repo_url,
+                    owner,
+                    repo_name,
+                    current_branch: Some(branch),
+                    repo_ref: Some(local_ref),
+                    latest_ref: Some(remote_ref),
+                    ..
+                } = &repo.source
+                    && local_ref != remote_ref
+                    && filter.matches(folder_path, repo_url, owner)
+                    && !config.is_excluded_from_bulk_update(folder_path, repo_url)
+                {
+                    Some(OutdatedRepo {
+                        folder_path: folder_path.clone(),
+                        repo_url: repo_url.clone(),
+                        repo_name: repo_name.clone(),
+                        branch: branch.clone(),
+                        local_ref: local_ref.clone(),
+                        remote_ref: remote_ref.clone(),
+                        behind: None,
+                    })
+                } else {
+                    None
+                }
+            })
+        })
+}
+
+/// The repositories `outdated_repos` reports, as (folder path, repo url, branch)
+pub(crate) fn pending_updates(
+    disk_state: &HashMap<String, DiskAddOnsFolder>,
+    config: &AddOnsUserConfig,
+    filter: &UpdateFilter,
+) -> Vec<(String, String, String)> {
+    outdated_git_repos(disk_state, config, filter)
+        .map(|repo| (repo.folder_path, repo.repo_url, repo.branch))
+        .collect()
+}
+
+/// A repository whose local branch is behind the last fetched remote ref
+#[derive(Debug, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "camelCase")]
+pub struct OutdatedRepo {
+    pub folder_path: String,
+    pub repo_url: String,
+    pub repo_name: String,
+    pub branch: String,
+    pub local_ref: String,
+    pub remote_ref: String,
+    /// Commits on the remote branch missing locally, if the history could be compared
+    #[ts(type = "number | null")]
+    pub behind: Option<usize>,
+}
+
+fn commits_behind(repo_dir: &Path, local: &str, remote: &str) -> Option<usize> {
+    let repo = Repository::open(repo_dir).ok()?;
+    let (local, remote) = (Oid::from_str(local).ok()?, Oid::from_str(remote).ok()?);
+    repo.graph_ahead_behind(local, remote)
+        .ok()
+        .map(|(_, behind)| behind)
+}
+
+/// The repositories `pending_updates` would update, with their refs and commit count
+pub fn outdated_repos(
+    folders: &[DiskAddOnsFolder],
+    config: &AddOnsUserConfig,
+    filter: &UpdateFilter,
+) -> Vec<OutdatedRepo> {
+    let folders = folders.iter().map(|folder| (&folder.path, folder));
+    outdated_git_repos(folders, config, filter)
+        .map(|mut repo| {
+            let repo_dir = Path::new(&repo.folder_path)
+                .join(".addonmanager")
+                .join(&repo.repo_name);
+            repo.behind = commits_behind(&repo_dir, &repo.local_ref, &repo.remote_ref);
+            repo
+        })
+        .collect()
+}
+
 /// Tauri command to update all addons across all folders
 #[tauri::command]
 pub async fn update_all_addons_cmd(
     app_handle: AppHandle,
     state: tauri::State<'_, AppState>,
+    registry: tauri::State<'_, OperationRegistry>,
+    filter: Option<UpdateFilter>,
 ) -> Result<(), String> {
+    let config = addon_store::load_user_config(&app_handle)?;
     let update_tasks = {
         let disk_state = state.get_disk_state()?;
-        let mut tasks = Vec::new();
-
-        for (folder_path, disk_folder) in disk_state.iter() {
-            for repo in &disk_folder.repositories {
-                // Only check for updates if it's a Git repository
-                if let crate::addon_disk::DiskAddonSource::Git {
-                    repo_url,
-                    current_branch,
-                    repo_ref,
-                    latest_ref,
-                    ..
-                } = &repo.source
-                    && let (Some(local_ref), Some(remote_ref)) = (repo_ref, latest_ref)
-                    && local_ref != remote_ref
-                    && let Some(branch) = current_branch
-                {
-                    tasks.push((folder_path.clone(), repo_url.clone(), branch.clone()));
-                }
-            }
-        }
-        tasks
+        pending_updates(&disk_state, &config, &filter.unwrap_or_default())
     };
 
     for (path, url, branch) in update_tasks {
-        let result = perform_update_op(&app_handle, url.clone(), path, branch).await;
+        let result = perform_update_op(&app_handle, &registry, url.clone(), path, branch).await;
         if let Ok(()) = result {
         } else if let Err(e) = result {
             eprintln!("Update failed for {url}: {e}");
@@ -168,3 +404,157 @@ pub async fn update_all_addons_cmd(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addon_disk::DiskAddonRepository;
+    use crate::test_utils::setup_addons_dir;
+    use crate::validate;
+
+    fn commit(repo: &Repository, message: &str) -> Oid {
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parents: Vec<_> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_outdated_repos_counts_commits_behind() {
+        let (_temp, addons_dir) = setup_addons_dir();
+        let repo_dir = validate::ensure_manager_dir(&addons_dir)
+            .unwrap()
+            .join("Repo");
+        let repo = Repository::init(&repo_dir).unwrap();
+        let mut refs = Vec::new();
+        for n in 0..3 {
+            std::fs::write(repo_dir.join("Repo.toc"), format!("## Version: {n}")).unwrap();
+            refs.push(commit(&repo, &format!("v{n}")).to_string());
+        }
+
+        let folder = |latest_ref: &str| DiskAddOnsFolder {
+            path: addons_dir.to_string_lossy().to_string(),
+            is_valid: true,
+            repositories: vec![DiskAddonRepository {
+                source: DiskAddonSource::Git {
+                    repo_url: "https://github.com/owner/Repo.git".to_string(),
+                    owner: "owner".to_string(),
+                    repo_name: "Repo".to_string(),
+                    current_branch: Some("main".to_string()),
+                    available_branches: Vec::new(),
+                    repo_ref: Some(refs[0].clone()),
+                    latest_ref: Some(latest_ref.to_string()),
+                    readme: None,
+                },
+                addons: Vec::new(),
+            }],
+            error: None,
+            orphans: Default::default(),
+        };
+
+        let config = AddOnsUserConfig::default();
+        let filter = UpdateFilter::default();
+        let outdated = outdated_repos(&[folder(&refs[2])], &config, &filter);
+        println!("{outdated:?}");
+        assert_eq!(outdated.len(), 1);
+        assert_eq!(outdated[0].behind, Some(2));
+        assert_eq!(outdated[0].remote_ref, refs[2]);
+        assert!(outdated_repos(&[folder(&refs[0])], &config, &filter).is_empty());
+    }
+
+    #[test]
+    fn test_update_filter() {
+        let folder = "/games/wow/Interface/AddOns";
+        let url = "https://github.com/Owner/Repo.git";
+        assert!(UpdateFilter::default().matches(folder, url, "Owner"));
+
+        let filter = UpdateFilter {
+            repos: vec!["*github.com/owner/*".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.matches(folder, url, "Owner"), "Globs ignore case");
+        assert!(!filter.matches(folder, "https://gitlab.com/owner/Repo.git", "owner"));
+
+        let filter = UpdateFilter {
+            folders: vec![folder.to_string()],
+            exclude_owners: vec!["owner".to_string()],
+            ..Default::default()
+        };
+        assert!(!filter.matches(folder, url, "Owner"), "Excludes win");
+        assert!(!filter.matches("/other/AddOns", url, "Other"));
+        assert!(filter.matches(folder, url, "Other"));
+
+        assert!(glob_matches("*/Repo.git", url));
+        assert!(glob_matches("https://github.com/?wner/*", url));
+        assert!(!glob_matches("*/Repo", url));
+    }
+
+    #[test]
+    fn test_pending_updates_skips_bulk_excluded_repos() {
+        let repo = |name: &str| DiskAddonRepository {
+            source: DiskAddonSource::Git {
+                repo_url: format!("https://github.com/owner/{name}.git"),
+                owner: "owner".to_string(),
+                repo_name: name.to_string(),
+                current_branch: Some("main".to_string()),
+                available_branches: Vec::new(),
+                repo_ref: Some("a".repeat(40)),
+                latest_ref: Some("b".repeat(40)),
+                readme: None,
+            },
+            addons: Vec::new(),
+        };
+        let path = "/games/wow/Interface/AddOns".to_string();
+        let disk_state = HashMap::from([(
+            path.clone(),
+            DiskAddOnsFolder {
+                path: path.clone(),
+                is_valid: true,
+                repositories: vec![repo("Kept"), repo("Pinned")],
+                error: None,
+                orphans: Default::default(),
+            },
+        )]);
+        let config = AddOnsUserConfig {
+            folders: vec![addon_store::AddOnsFolderUserMeta {
+                path: path.clone(),
+                repos: vec![addon_store::AddonRepositoryUserMeta {
+                    repo_url: "https://github.com/owner/Pinned.git".to_string(),
+                    addons: HashMap::new(),
+                    exclude_from_bulk_update: true,
+                    auto_update: false,
+                }],
+                settings: Default::default(),
+            }],
+            ..Default::default()
+        };
+
+        let tasks = pending_updates(&disk_state, &config, &UpdateFilter::default());
+        assert_eq!(
+            tasks,
+            vec![(
+                path,
+                "https://github.com/owner/Kept.git".to_string(),
+                "main".to_string()
+            )]
+        );
+    }
+}
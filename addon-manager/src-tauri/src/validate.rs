@@ -2,6 +2,8 @@ use std::path::{Path, PathBuf};
 
 use regex::Regex;
 
+use crate::error::{AddonError, AddonErrorKind};
+
 /// Returns true if the string matches a valid HTTP(S) git URL ending with .git
 ///
 /// # Examples
@@ -64,15 +66,19 @@ pub fn is_valid_addons_folder_str(path: &str) -> bool {
 /// assert!(path.exists());
 /// assert!(path.ends_with(".addonmanager"));
 /// ```
-pub fn ensure_manager_dir(base_dir: &Path) -> Result<PathBuf, String> {
+pub fn ensure_manager_dir(base_dir: &Path) -> Result<PathBuf, AddonError> {
     if !base_dir.is_dir() {
-        return Err("Game path does not exist".to_string());
+        return Err(
+            AddonError::new(AddonErrorKind::NotFound, "Game path does not exist")
+                .with_path(base_dir),
+        );
     }
 
     let manager_dir = base_dir.join(".addonmanager");
     if !manager_dir.exists() {
-        std::fs::create_dir(&manager_dir)
-            .map_err(|e| format!("Failed to create manager dir: {e}"))?;
+        std::fs::create_dir(&manager_dir).map_err(|e| {
+            AddonError::io("Failed to create manager dir", &e).with_path(&manager_dir)
+        })?;
     }
 
     Ok(manager_dir)
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+use crate::{addon_store::FolderSettings, orphans::OrphanReport};
+
 #[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[ts(export)]
 #[serde(rename_all = "camelCase")]
@@ -52,4 +54,6 @@ pub struct AddOnsFolder {
     pub is_valid: bool,
     pub error: Option<String>,
     pub repositories: Vec<AddonRepository>,
+    pub orphans: OrphanReport,
+    pub settings: FolderSettings,
 }
@@ -3,6 +3,7 @@ import { listen } from '@tauri-apps/api/event'
 import type { OperationKey } from '@bindings/OperationKey'
 import type { OperationEventPayload } from '@bindings/OperationEventPayload'
 import type { OperationType } from '@bindings/OperationType'
+import type { AddonErrorKind } from '@bindings/AddonErrorKind'
 
 export interface OperationState {
     type?: OperationType
@@ -10,6 +11,7 @@ export interface OperationState {
     status?: string
     warning?: string
     error?: string
+    errorKind?: AddonErrorKind
     isActive: boolean
 }
 
@@ -95,14 +97,15 @@ export function useOperationTracker() {
 
             if (typeof event === 'object' && 'error' in event) {
                 const current = operations.get(keyString) || { isActive: true }
-                current.error = event.error
+                current.error = event.error.message
+                current.errorKind = event.error.kind
                 current.warning = undefined
                 current.isActive = false
                 operations.set(keyString, current)
                 const repoName = extractRepoName(key.repoUrl)
                 recentlyCompleted.value.push({
                     keyString,
-                    type: event.error,
+                    type: event.error.message,
                     time: Date.now(),
                     repoName,
                 })
@@ -138,6 +141,19 @@ export function useOperationTracker() {
                     )
                 }, recentlyCompletedDelay)
 
+                const operationsCleanupDelay = 1000
+                setTimeout(() => {
+                    operations.delete(keyString)
+                }, operationsCleanupDelay)
+            } else if (event === 'cancelled') {
+                const current = operations.get(keyString) || { isActive: true }
+                current.status = 'Cancelled'
+                current.progress = undefined
+                current.warning = undefined
+                current.error = undefined
+                current.isActive = false
+                operations.set(keyString, current)
+
                 const operationsCleanupDelay = 1000
                 setTimeout(() => {
                     operations.delete(keyString)